@@ -52,5 +52,8 @@ fn main() {
         }
     }
 
-    println!("cargo:rustc-env=RATATUI_TEMPLATE_GIT_INFO={}", git_describe);
+    println!(
+        "cargo:rustc-env=HYPRLAND_WINDOW_RULE_GIT_INFO={}",
+        git_describe
+    );
 }
@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+
+use hyprland::data::Client;
+use serde::Serialize;
+
+use crate::desktop_entry::{self, DesktopEntry};
+use crate::process_info::{self, Sandbox};
+
+/// Which app a window belongs to, everything is optional since windows of other users, closed
+/// windows and windows from a snapshot can't be looked up
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppInfo {
+    /// The desktop entry's `Name`
+    pub name: Option<String>,
+    pub icon: Option<String>,
+    pub desktop_file: Option<PathBuf>,
+    pub executable: Option<PathBuf>,
+    pub sandbox: Option<Sandbox>,
+}
+
+impl AppInfo {
+    fn from_entry(entry: Option<&DesktopEntry>) -> Self {
+        return Self {
+            name: entry.map(|entry| entry.name.clone()),
+            icon: entry.and_then(|entry| entry.icon.clone()),
+            desktop_file: entry.map(|entry| entry.path.clone()),
+            ..Self::default()
+        };
+    }
+
+    /// `Firefox, icon firefox, flatpak org.mozilla.firefox`, `None` when nothing was found
+    pub fn summary(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.name.clone(),
+            self.icon.as_ref().map(|icon| format!("icon {}", icon)),
+            self.sandbox.as_ref().map(|sandbox| match sandbox {
+                Sandbox::Flatpak(app_id) => format!("flatpak {}", app_id),
+                Sandbox::Snap(name) => format!("snap {}", name),
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        return (!parts.is_empty()).then(|| parts.join(", "));
+    }
+}
+
+/// Looks up the process of `client` in `proc_dir` and its desktop entry in `entries`
+pub fn resolve_app(proc_dir: &Path, entries: &[DesktopEntry], client: &Client) -> AppInfo {
+    let executable = process_info::executable(proc_dir, client.pid).ok();
+    let sandbox = process_info::sandbox(proc_dir, client.pid);
+    let entry = desktop_entry::find_desktop_entry(
+        entries,
+        &client.class,
+        sandbox.as_ref(),
+        executable.as_deref(),
+    );
+    return AppInfo {
+        executable,
+        sandbox,
+        ..AppInfo::from_entry(entry)
+    };
+}
+
+/// For when only the class is known, like in window events
+pub fn resolve_class(entries: &[DesktopEntry], class: &str) -> AppInfo {
+    return AppInfo::from_entry(desktop_entry::find_desktop_entry(
+        entries, class, None, None,
+    ));
+}
@@ -0,0 +1,128 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use color_eyre::eyre;
+use directories::UserDirs;
+use hyprland::data::Client;
+use serde::Deserialize;
+
+use crate::hyprland_config::{Parameter, WindowMode};
+use crate::system_info;
+
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Which keyword window rules are written with
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSyntax {
+    /// `windowrule = float, class:...`, Hyprland 0.48 and newer
+    #[default]
+    WindowRule,
+    /// `windowrulev2 = float, class:...` for older Hyprland versions
+    #[serde(alias = "v2")]
+    WindowRuleV2,
+}
+
+/// Window properties the generated rules match on
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Matcher {
+    Class,
+    Title,
+    InitialClass,
+    InitialTitle,
+}
+
+impl Matcher {
+    pub fn parameter_for(&self, client: &Client) -> Parameter {
+        return match self {
+            Self::Class => Parameter::Class(client.class.clone()),
+            Self::Title => Parameter::title_of(client),
+            Self::InitialClass => Parameter::InitialClass(client.initial_class.clone()),
+            Self::InitialTitle => Parameter::initial_title_of(client),
+        };
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub syntax: RuleSyntax,
+    /// When not set a matcher is picked for each window, see `matcher_suggestion`
+    pub matchers: Option<Vec<Matcher>>,
+    /// File the rules get written to, relative paths are relative to the Hyprland config directory
+    pub rules_file: Option<PathBuf>,
+    /// Reload Hyprland after writing to the rules file
+    pub auto_reload: bool,
+    /// Named lists of rules like `"pip": ["float", "pin", "size 25% 25%"]`
+    pub presets: HashMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        return Self {
+            syntax: RuleSyntax::default(),
+            matchers: None,
+            rules_file: None,
+            auto_reload: true,
+            presets: HashMap::new(),
+        };
+    }
+}
+
+impl Config {
+    pub fn parameters_for(&self, client: &Client) -> Option<Vec<Parameter>> {
+        let matchers = self.matchers.as_ref()?;
+        return Some(
+            matchers
+                .iter()
+                .map(|matcher| matcher.parameter_for(client))
+                .collect(),
+        );
+    }
+
+    pub fn preset(&self, name: &str) -> eyre::Result<Vec<WindowMode>> {
+        let Some(preset) = self.presets.get(name) else {
+            let mut names: Vec<&String> = self.presets.keys().collect();
+            names.sort();
+            return Err(eyre::eyre!(
+                "No preset called {} in {}, the presets are: {:?}",
+                name,
+                get_config_file()?.display(),
+                names
+            ));
+        };
+        return preset
+            .iter()
+            .map(|mode| {
+                mode.parse::<WindowMode>()
+                    .map_err(|error| eyre::eyre!("In preset {}, `{}`: {}", name, mode, error))
+            })
+            .collect();
+    }
+
+    /// `rules_file` with `~` expanded
+    pub fn rules_file(&self) -> Option<PathBuf> {
+        let path = self.rules_file.as_ref()?;
+        if let Ok(relative) = path.strip_prefix("~")
+            && let Some(user_dirs) = UserDirs::new()
+        {
+            return Some(user_dirs.home_dir().join(relative));
+        }
+        return Some(path.clone());
+    }
+}
+
+pub fn get_config_file() -> eyre::Result<PathBuf> {
+    return Ok(system_info::get_config_dir()?.join(CONFIG_FILE_NAME));
+}
+
+/// Reads the config file, everything falls back to the defaults when there isn't one
+pub fn load_config() -> eyre::Result<Config> {
+    let config_file = get_config_file()?;
+    if !config_file.exists() {
+        return Ok(Config::default());
+    }
+    let contents = fs::read_to_string(&config_file)?;
+    return serde_json::from_str(&contents)
+        .map_err(|error| eyre::eyre!("Invalid config in {}: {}", config_file.display(), error));
+}
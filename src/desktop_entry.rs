@@ -0,0 +1,324 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use directories::BaseDirs;
+use serde::Serialize;
+
+use crate::process_info::Sandbox;
+
+/// The parts of a `.desktop` file that say which app it is
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub icon: Option<String>,
+    pub startup_wm_class: Option<String>,
+    pub exec: Option<String>,
+}
+
+impl DesktopEntry {
+    /// `org.mozilla.firefox` for `org.mozilla.firefox.desktop`
+    pub fn id(&self) -> String {
+        return self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+
+    /// File name of the program `Exec` starts, `env` and the variables it sets are skipped
+    fn exec_program(&self) -> Option<String> {
+        let program = self
+            .exec
+            .as_ref()?
+            .split_whitespace()
+            .find(|word| *word != "env" && !word.contains('='))?;
+        return Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+    }
+}
+
+/// Where desktop files are looked for, `$XDG_DATA_HOME` and `$XDG_DATA_DIRS` like the spec says plus
+/// the Flatpak and Snap export directories in case the session didn't add them
+pub fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    match env::var("XDG_DATA_HOME") {
+        Ok(data_home) if !data_home.is_empty() => dirs.push(PathBuf::from(data_home)),
+        _ => dirs.extend(BaseDirs::new().map(|base| base.data_dir().to_path_buf())),
+    }
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|data_dirs| !data_dirs.is_empty())
+        .unwrap_or("/usr/local/share:/usr/share".to_string());
+    dirs.extend(data_dirs.split(':').map(PathBuf::from));
+    dirs.extend(BaseDirs::new().map(|base| base.data_dir().join("flatpak/exports/share")));
+    dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    dirs.push(PathBuf::from("/var/lib/snapd/desktop"));
+
+    let mut unique: Vec<PathBuf> = vec![];
+    for dir in dirs {
+        if !unique.contains(&dir) {
+            unique.push(dir);
+        }
+    }
+    return unique;
+}
+
+/// Reads the `[Desktop Entry]` group, localized keys are skipped
+pub fn parse_desktop_entry(path: &Path, contents: &str) -> Option<DesktopEntry> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut icon = None;
+    let mut startup_wm_class = None;
+    let mut exec = None;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if !in_entry {
+            continue;
+        }
+        let value = Some(value.trim().to_string());
+        match key.trim() {
+            "Name" => name = value,
+            "Icon" => icon = value,
+            "StartupWMClass" => startup_wm_class = value,
+            "Exec" => exec = value,
+            _ => (),
+        }
+    }
+    return Some(DesktopEntry {
+        path: path.to_path_buf(),
+        name: name?,
+        icon,
+        startup_wm_class,
+        exec,
+    });
+}
+
+/// Every desktop file in the `applications` directory of `data_dirs`, the first one with a given
+/// id wins like it does for launchers
+pub fn read_desktop_entries(data_dirs: &[PathBuf]) -> Vec<DesktopEntry> {
+    let mut entries: Vec<DesktopEntry> = vec![];
+    for dir in data_dirs {
+        let Ok(files) = fs::read_dir(dir.join("applications")) else {
+            continue;
+        };
+        for file in files.flatten() {
+            let path = file.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "desktop")
+            {
+                continue;
+            }
+            let Some(entry) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| parse_desktop_entry(&path, &contents))
+            else {
+                continue;
+            };
+            if entries.iter().all(|found| found.id() != entry.id()) {
+                entries.push(entry);
+            }
+        }
+    }
+    return entries;
+}
+
+/// The desktop entry for a window of `class`, by `StartupWMClass` first, then the file name,
+/// then the sandboxed app's id and last the program `Exec` starts
+pub fn find_desktop_entry<'a>(
+    entries: &'a [DesktopEntry],
+    class: &str,
+    sandbox: Option<&Sandbox>,
+    executable: Option<&Path>,
+) -> Option<&'a DesktopEntry> {
+    let class = class.to_lowercase();
+    let by_wm_class = entries.iter().find(|entry| {
+        entry
+            .startup_wm_class
+            .as_ref()
+            .is_some_and(|wm_class| wm_class.to_lowercase() == class)
+    });
+    let by_id = || {
+        entries.iter().find(|entry| {
+            let id = entry.id().to_lowercase();
+            !class.is_empty() && (id == class || id.ends_with(&format!(".{}", class)))
+        })
+    };
+    let by_sandbox = || {
+        entries.iter().find(|entry| match sandbox {
+            Some(Sandbox::Flatpak(app_id)) => entry.id() == *app_id,
+            // snapd exports them as `<snap>_<app>.desktop`
+            Some(Sandbox::Snap(name)) => entry.id().starts_with(&format!("{}_", name)),
+            None => false,
+        })
+    };
+    let by_exec = || {
+        let program = executable?.file_name()?.to_string_lossy().to_string();
+        return entries
+            .iter()
+            .find(|entry| entry.exec_program().as_ref() == Some(&program));
+    };
+    return by_wm_class
+        .or_else(by_id)
+        .or_else(by_sandbox)
+        .or_else(by_exec);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Data directories with `applications/` holding `files`, each one is `(data dir, file name, contents)`
+    fn data_dirs(files: &[(usize, &str, &str)]) -> (tempfile::TempDir, Vec<PathBuf>) {
+        let root = tempfile::tempdir().unwrap();
+        let dirs: Vec<PathBuf> = (0..2)
+            .map(|index| root.path().join(index.to_string()))
+            .collect();
+        for (dir, name, contents) in files {
+            let applications = dirs[*dir].join("applications");
+            fs::create_dir_all(&applications).unwrap();
+            fs::write(applications.join(name), contents).unwrap();
+        }
+        return (root, dirs);
+    }
+
+    fn find(entries: &[DesktopEntry], class: &str) -> Option<String> {
+        return find_desktop_entry(entries, class, None, None).map(|entry| entry.id());
+    }
+
+    #[test]
+    fn startup_wm_class_comes_before_the_file_name() {
+        let (_root, dirs) = data_dirs(&[
+            (
+                0,
+                "jetbrains-idea.desktop",
+                "[Desktop Entry]\nName=Old IDEA\n",
+            ),
+            (
+                0,
+                "idea.desktop",
+                "[Desktop Entry]\nName=IntelliJ IDEA\nStartupWMClass=jetbrains-idea\n",
+            ),
+        ]);
+        let entries = read_desktop_entries(&dirs);
+
+        assert_eq!(find(&entries, "jetbrains-idea").as_deref(), Some("idea"));
+    }
+
+    #[test]
+    fn classes_match_regardless_of_case() {
+        let (_root, dirs) = data_dirs(&[
+            (0, "firefox.desktop", "[Desktop Entry]\nName=Firefox\n"),
+            (
+                0,
+                "nautilus.desktop",
+                "[Desktop Entry]\nName=Files\nStartupWMClass=org.gnome.nautilus\n",
+            ),
+            (
+                0,
+                "org.kde.dolphin.desktop",
+                "[Desktop Entry]\nName=Dolphin\n",
+            ),
+        ]);
+        let entries = read_desktop_entries(&dirs);
+
+        assert_eq!(find(&entries, "Firefox").as_deref(), Some("firefox"));
+        assert_eq!(
+            find(&entries, "org.gnome.Nautilus").as_deref(),
+            Some("nautilus")
+        );
+        // reverse DNS ids end in the class
+        assert_eq!(
+            find(&entries, "Dolphin").as_deref(),
+            Some("org.kde.dolphin")
+        );
+        assert_eq!(find(&entries, "kitty"), None);
+        assert_eq!(find(&entries, ""), None);
+    }
+
+    #[test]
+    fn sandbox_and_exec_are_the_last_resort() {
+        let (_root, dirs) = data_dirs(&[
+            (
+                0,
+                "org.mozilla.firefox.desktop",
+                "[Desktop Entry]\nName=Firefox\n",
+            ),
+            (
+                0,
+                "spotify_spotify.desktop",
+                "[Desktop Entry]\nName=Spotify\n",
+            ),
+            (
+                0,
+                "btop.desktop",
+                "[Desktop Entry]\nName=btop++\nExec=env TERM=xterm /usr/bin/btop --utf-force\n",
+            ),
+        ]);
+        let entries = read_desktop_entries(&dirs);
+
+        let flatpak = Sandbox::Flatpak("org.mozilla.firefox".to_string());
+        let found = find_desktop_entry(&entries, "Navigator", Some(&flatpak), None);
+        assert_eq!(
+            found.map(|entry| entry.id()).as_deref(),
+            Some("org.mozilla.firefox")
+        );
+
+        let snap = Sandbox::Snap("spotify".to_string());
+        let found = find_desktop_entry(&entries, "", Some(&snap), None);
+        assert_eq!(
+            found.map(|entry| entry.id()).as_deref(),
+            Some("spotify_spotify")
+        );
+
+        let found = find_desktop_entry(&entries, "foot", None, Some(Path::new("/usr/bin/btop")));
+        assert_eq!(found.map(|entry| entry.name.as_str()), Some("btop++"));
+    }
+
+    #[test]
+    fn the_first_data_dir_wins_and_other_files_are_skipped() {
+        let (_root, dirs) = data_dirs(&[
+            (0, "foot.desktop", "[Desktop Entry]\nName=Foot (local)\n"),
+            (1, "foot.desktop", "[Desktop Entry]\nName=Foot\n"),
+            (1, "notes.txt", "[Desktop Entry]\nName=Not an app\n"),
+            // no name in the main group, the action's doesn't count
+            (
+                1,
+                "broken.desktop",
+                "[Desktop Entry]\nIcon=x\n[Desktop Action new]\nName=New\n",
+            ),
+            (
+                1,
+                "kitty.desktop",
+                "[Desktop Entry]\nName[de]=Kätzchen\nName=kitty\nIcon=kitty\n",
+            ),
+        ]);
+
+        let mut entries = read_desktop_entries(&dirs);
+        entries.sort_by_key(|entry| entry.id());
+
+        let names: Vec<(String, &str)> = entries
+            .iter()
+            .map(|entry| (entry.id(), entry.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("foot".to_string(), "Foot (local)"),
+                ("kitty".to_string(), "kitty"),
+            ]
+        );
+        assert_eq!(entries[1].icon.as_deref(), Some("kitty"));
+    }
+}
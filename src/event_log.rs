@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hyprland::data::Client;
+use hyprland::shared::Address;
+use serde::Serialize;
+
+use crate::app_info::{self, AppInfo};
+use crate::desktop_entry::{self, DesktopEntry};
+use crate::rules_file::{ConfigRule, WindowMatch};
+use crate::window_events::WindowEvent;
+
+/// A window rule that's for the window's class, `fired` when the rest of its matchers match too
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuleHit {
+    pub file: PathBuf,
+    pub line_number: usize,
+    pub rule: String,
+    pub fired: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LoggedEvent {
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    /// Named after Hyprland's event, `openwindow`, `windowtitle`...
+    pub event: &'static str,
+    pub address: String,
+    pub class: String,
+    pub title: String,
+    /// What changed, the new title or workspace
+    pub detail: String,
+    /// The app's name and icon from its desktop entry
+    pub app: AppInfo,
+    pub rules: Vec<RuleHit>,
+}
+
+/// Keeps track of what the open windows look like so each event can be matched against the rules
+pub struct EventLog {
+    windows: HashMap<Address, WindowMatch>,
+    focused: Option<Address>,
+    rules: Vec<ConfigRule>,
+    desktop_entries: Vec<DesktopEntry>,
+}
+
+impl EventLog {
+    pub fn new(clients: &[Client], rules: Vec<ConfigRule>) -> Self {
+        return Self {
+            windows: clients
+                .iter()
+                .map(|client| (client.address.clone(), WindowMatch::from(client)))
+                .collect(),
+            focused: None,
+            rules,
+            desktop_entries: desktop_entry::read_desktop_entries(&desktop_entry::data_dirs()),
+        };
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<ConfigRule>) {
+        self.rules = rules;
+    }
+
+    fn rule_hits(&self, window: &WindowMatch) -> Vec<RuleHit> {
+        return self
+            .rules
+            .iter()
+            .filter(|rule| rule.line.matches_class(window) || rule.line.matches_window(window))
+            .map(|rule| RuleHit {
+                file: rule.file.clone(),
+                line_number: rule.line.line_number,
+                rule: rule.line.value.clone(),
+                fired: rule.line.matches_window(window),
+            })
+            .collect();
+    }
+
+    /// Updates the windows with `event`, events that aren't about a window we know of are left out
+    pub fn log(&mut self, event: WindowEvent) -> Option<LoggedEvent> {
+        let (name, address, detail) = match event {
+            WindowEvent::Opened(opened) => {
+                self.windows.insert(
+                    opened.window_address.clone(),
+                    WindowMatch {
+                        class: Some(opened.window_class.clone()),
+                        title: Some(opened.window_title.clone()),
+                        initial_class: Some(opened.window_class),
+                        initial_title: Some(opened.window_title),
+                    },
+                );
+                (
+                    "openwindow",
+                    opened.window_address,
+                    format!("workspace {}", opened.workspace_name),
+                )
+            }
+            WindowEvent::Closed(address) => ("closewindow", address, String::new()),
+            WindowEvent::TitleChanged(changed) => {
+                let window = self.windows.get_mut(&changed.address)?;
+                window.title = Some(changed.title.clone());
+                ("windowtitle", changed.address, changed.title)
+            }
+            WindowEvent::FloatChanged(changed) => {
+                let detail = if changed.floating {
+                    "floating"
+                } else {
+                    "tiled"
+                };
+                ("changefloatingmode", changed.address, detail.to_string())
+            }
+            WindowEvent::Moved(moved) => (
+                "movewindow",
+                moved.window_address,
+                format!("workspace {}", moved.workspace_name),
+            ),
+            WindowEvent::Fullscreen(state) => {
+                let detail = if state { "on" } else { "off" };
+                ("fullscreen", self.focused.clone()?, detail.to_string())
+            }
+            WindowEvent::Focused(address) => {
+                self.focused = address;
+                return None;
+            }
+            WindowEvent::ConfigReloaded => return None,
+        };
+
+        let window = self.windows.get(&address)?.clone();
+        if name == "closewindow" {
+            self.windows.remove(&address);
+        }
+        return Some(LoggedEvent {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+            event: name,
+            address: address.to_string(),
+            rules: self.rule_hits(&window),
+            app: app_info::resolve_class(
+                &self.desktop_entries,
+                window.class.as_deref().unwrap_or_default(),
+            ),
+            class: window.class.unwrap_or_default(),
+            title: window.title.unwrap_or_default(),
+            detail,
+        });
+    }
+}
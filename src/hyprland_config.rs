@@ -1,67 +1,314 @@
 use std::{
-    fmt::{Display, Error, Formatter, Result as FmtResult},
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs,
+    io::Write,
     num::ParseIntError,
+    path::{Path, PathBuf},
+    process::Command,
     str::FromStr,
 };
 
+use crate::config::{self, RuleSyntax};
+use crate::snapshot;
 use crate::system_info;
+use crate::title_normalizer;
 use color_eyre::eyre;
-use hyprland::data::Client;
+use hyprland::ctl::reload;
+use hyprland::data::{Client, WorkspaceBasic};
+use regex::Regex;
 
-enum WindowMode {
+/// A size or position value in a rule, percentages are relative to the monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Length {
+    Pixels(i32),
+    Percent(i32),
+}
+
+impl Display for Length {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Pixels(pixels) => write!(f, "{}", pixels),
+            Self::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceTarget {
+    Id(i32),
+    Name(String),
+    /// `None` is the default special workspace
+    Special(Option<String>),
+}
+
+impl From<&WorkspaceBasic> for WorkspaceTarget {
+    fn from(workspace: &WorkspaceBasic) -> Self {
+        if let Some(special) = workspace.name.strip_prefix("special") {
+            return Self::Special(special.strip_prefix(':').map(|name| name.to_string()));
+        }
+        if workspace.name == workspace.id.to_string() {
+            return Self::Id(workspace.id);
+        }
+        return Self::Name(workspace.name.clone());
+    }
+}
+
+impl Display for WorkspaceTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Id(id) => write!(f, "{}", id),
+            Self::Name(name) => write!(f, "name:{}", name),
+            Self::Special(Some(name)) => write!(f, "special:{}", name),
+            Self::Special(None) => write!(f, "special"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowMode {
     Tile,
     Float,
+    Fullscreen,
+    Pin,
+    PersistentSize,
+    Size(Length, Length),
+    Move(Length, Length),
+    Center,
+    /// The bool is for opening the window without switching to the workspace
+    Workspace(WorkspaceTarget, bool),
+    /// Connector name or `desc:` followed by the monitor description
+    Monitor(String),
+    NoAnim,
+    /// Layer only
+    Blur,
+    /// Layer only, makes blur ignore fully transparent pixels
+    IgnoreZero,
 }
 
-impl ToString for WindowMode {
-    fn to_string(&self) -> String {
+impl WindowMode {
+    pub fn applies_to(&self, kind: &RuleKind) -> bool {
+        return match (self, kind) {
+            (Self::NoAnim | Self::Blur | Self::IgnoreZero, RuleKind::Layer) => true,
+            (Self::Blur | Self::IgnoreZero, RuleKind::Window) => false,
+            (_, RuleKind::Window) => true,
+            (_, RuleKind::Layer) => false,
+        };
+    }
+}
+
+impl Display for WindowMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
-            Self::Float => "float".to_string(),
-            Self::Tile => "tile".to_string(),
+            Self::Float => write!(f, "float"),
+            Self::Tile => write!(f, "tile"),
+            Self::Fullscreen => write!(f, "fullscreen"),
+            Self::Pin => write!(f, "pin"),
+            Self::PersistentSize => write!(f, "persistentsize"),
+            Self::Size(width, height) => write!(f, "size {} {}", width, height),
+            Self::Move(x, y) => write!(f, "move {} {}", x, y),
+            Self::Center => write!(f, "center"),
+            Self::Workspace(workspace, false) => write!(f, "workspace {}", workspace),
+            Self::Workspace(workspace, true) => write!(f, "workspace {} silent", workspace),
+            Self::Monitor(monitor) => write!(f, "monitor {}", monitor),
+            Self::NoAnim => write!(f, "noanim"),
+            Self::Blur => write!(f, "blur"),
+            Self::IgnoreZero => write!(f, "ignorezero"),
         }
     }
 }
 
-enum Parameter {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Parameter {
     Class(String),
     Title(String),
     InitialClass(String),
     InitialTitle(String),
+    /// Layer surface namespace, layer rules take it without a field name
+    Namespace(String),
+}
+
+impl Parameter {
+    /// Title matcher that keeps matching when the dynamic parts of the title change
+    pub fn title_of(client: &Client) -> Self {
+        return Self::Title(title_normalizer::normalize(
+            &client.title,
+            &client.initial_class,
+        ));
+    }
+
+    pub fn initial_title_of(client: &Client) -> Self {
+        return Self::InitialTitle(title_normalizer::normalize(
+            &client.initial_title,
+            &client.initial_class,
+        ));
+    }
 }
 
-impl ToString for Parameter {
-    fn to_string(&self) -> String {
+impl Display for Parameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
-            Self::Class(class) => class.clone(),
-            Self::Title(title) => title.clone(),
-            Self::InitialClass(initial_class) => initial_class.clone(),
-            Self::InitialTitle(initial_title) => initial_title.clone(),
+            Self::Class(class) => write!(f, "class:{}", class),
+            Self::Title(title) => write!(f, "title:{}", title),
+            Self::InitialClass(initial_class) => write!(f, "initialClass:{}", initial_class),
+            Self::InitialTitle(initial_title) => write!(f, "initialTitle:{}", initial_title),
+            Self::Namespace(namespace) => write!(f, "{}", namespace),
         }
     }
 }
 
-struct WindowRule {
-    mode: WindowMode,
-    parameters: Vec<Parameter>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleKind {
+    Window,
+    Layer,
 }
 
-impl Display for WindowRule {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowRule {
+    pub kind: RuleKind,
+    pub syntax: RuleSyntax,
+    pub mode: WindowMode,
+    pub parameters: Vec<Parameter>,
+}
+
+impl WindowRule {
+    pub fn keyword(&self) -> &'static str {
+        return match (&self.kind, self.syntax) {
+            (RuleKind::Window, RuleSyntax::WindowRule) => "windowrule",
+            (RuleKind::Window, RuleSyntax::WindowRuleV2) => "windowrulev2",
+            (RuleKind::Layer, _) => "layerrule",
+        };
+    }
+
+    /// Everything on the right hand side of the `=`, this is also what `hyprctl keyword` expects as the value
+    pub fn value(&self) -> String {
         let parameters = self
             .parameters
             .iter()
             .map(|param| param.to_string())
             .collect::<Vec<String>>()
-            .join("\n");
+            .join(", ");
+
+        return format!("{}, {}", self.mode, parameters);
+    }
+}
+
+impl Display for WindowRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{} = {}", self.keyword(), self.value());
+    }
+}
+
+impl From<&str> for WorkspaceTarget {
+    /// Same forms a workspace rule takes, `3`, `name:code`, `special:scratch` or a bare name
+    fn from(workspace: &str) -> Self {
+        if let Ok(id) = workspace.parse::<i32>() {
+            return Self::Id(id);
+        }
+        if let Some(special) = workspace.strip_prefix("special") {
+            return Self::Special(special.strip_prefix(':').map(|name| name.to_string()));
+        }
+        let name = workspace.strip_prefix("name:").unwrap_or(workspace);
+        return Self::Name(name.to_string());
+    }
+}
 
-        return write!(f, "windowrule = {}, {}", self.mode.to_string(), parameters,);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceOption {
+    /// Connector name or `desc:` followed by the monitor description
+    Monitor(String),
+    /// Workspace the monitor shows when Hyprland starts
+    Default,
+    GapsIn(u32),
+    GapsOut(u32),
+    BorderSize(u32),
+    Border(bool),
+    Rounding(bool),
+    /// Keeps the workspace around even when it has no windows
+    Persistent,
+    /// Command to run when the workspace gets created without any windows on it
+    OnCreatedEmpty(String),
+}
+
+impl Display for WorkspaceOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Monitor(monitor) => write!(f, "monitor:{}", monitor),
+            Self::Default => write!(f, "default:true"),
+            Self::GapsIn(gaps) => write!(f, "gapsin:{}", gaps),
+            Self::GapsOut(gaps) => write!(f, "gapsout:{}", gaps),
+            Self::BorderSize(size) => write!(f, "bordersize:{}", size),
+            Self::Border(border) => write!(f, "border:{}", border),
+            Self::Rounding(rounding) => write!(f, "rounding:{}", rounding),
+            Self::Persistent => write!(f, "persistent:true"),
+            Self::OnCreatedEmpty(command) => write!(f, "on-created-empty:{}", command),
+        }
+    }
+}
+
+/// Unlike window rules all the options for a workspace go on a single line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRule {
+    pub workspace: WorkspaceTarget,
+    pub options: Vec<WorkspaceOption>,
+}
+
+impl WorkspaceRule {
+    pub fn keyword(&self) -> &'static str {
+        return "workspace";
+    }
+
+    pub fn value(&self) -> String {
+        return std::iter::once(self.workspace.to_string())
+            .chain(self.options.iter().map(|option| option.to_string()))
+            .collect::<Vec<String>>()
+            .join(", ");
+    }
+}
+
+impl Display for WorkspaceRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        return write!(f, "{} = {}", self.keyword(), self.value());
+    }
+}
+
+/// Starts an app with rules that only apply to the windows it opens, `exec-once = [float; size 50% 50%] kitty`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecRule {
+    pub modes: Vec<WindowMode>,
+    /// Run with `sh -c`
+    pub command: String,
+}
+
+impl ExecRule {
+    pub fn keyword(&self) -> &'static str {
+        return "exec-once";
+    }
+
+    /// `#` starts a comment in the config unless it's doubled
+    pub fn value(&self) -> String {
+        let command = self.command.replace('#', "##");
+        if self.modes.is_empty() {
+            return command;
+        }
+        let modes: Vec<String> = self.modes.iter().map(|mode| mode.to_string()).collect();
+        return format!("[{}] {}", modes.join("; "), command);
+    }
+}
+
+impl Display for ExecRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        return write!(f, "{} = {}", self.keyword(), self.value());
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseRuleError {
-    /// The string does not follow the expected “x,y wxh” layout.
+    /// A known rule with missing or extra values, like `size 800`
     InvalidFormat,
+    /// Not a rule effect this tool knows about
+    UnknownMode(String),
+    /// A `field:value` matcher that isn't one of the `Parameter`s
+    UnknownMatcher(String),
     /// One of the integer components could not be parsed.
     InvalidNumber(ParseIntError),
 }
@@ -69,7 +316,9 @@ pub enum ParseRuleError {
 impl std::fmt::Display for ParseRuleError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
-            ParseRuleError::InvalidFormat => write!(f, "invalid region format"),
+            ParseRuleError::InvalidFormat => write!(f, "invalid rule format"),
+            ParseRuleError::UnknownMode(mode) => write!(f, "unknown rule: {}", mode),
+            ParseRuleError::UnknownMatcher(field) => write!(f, "unknown matcher: {}", field),
             ParseRuleError::InvalidNumber(e) => write!(f, "invalid number: {}", e),
         }
     }
@@ -83,12 +332,271 @@ impl From<ParseIntError> for ParseRuleError {
     }
 }
 
-pub fn generate_config_for(client: &Client) -> eyre::Result<()> {
+impl FromStr for Length {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.strip_suffix('%') {
+            Some(percent) => Ok(Self::Percent(percent.parse()?)),
+            None => Ok(Self::Pixels(s.parse()?)),
+        };
+    }
+}
+
+impl FromStr for Parameter {
+    type Err = ParseRuleError;
+
+    /// Window rule matchers only, layer namespaces don't have a field name to go by
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((field, value)) = s.trim().split_once(':') else {
+            return Err(ParseRuleError::InvalidFormat);
+        };
+        let value = value.to_string();
+        return match field {
+            "class" => Ok(Self::Class(value)),
+            "title" => Ok(Self::Title(value)),
+            "initialClass" | "initialclass" => Ok(Self::InitialClass(value)),
+            "initialTitle" | "initialtitle" => Ok(Self::InitialTitle(value)),
+            _ => Err(ParseRuleError::UnknownMatcher(field.to_string())),
+        };
+    }
+}
+
+impl FromStr for WindowMode {
+    type Err = ParseRuleError;
+
+    /// Takes the same form `Display` writes, like `float` or `size 25% 25%`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        return match words.as_slice() {
+            ["tile"] => Ok(Self::Tile),
+            ["float"] => Ok(Self::Float),
+            ["fullscreen"] => Ok(Self::Fullscreen),
+            ["pin"] => Ok(Self::Pin),
+            ["persistentsize"] => Ok(Self::PersistentSize),
+            ["center"] => Ok(Self::Center),
+            ["noanim"] => Ok(Self::NoAnim),
+            ["blur"] => Ok(Self::Blur),
+            ["ignorezero"] => Ok(Self::IgnoreZero),
+            ["size", width, height] => Ok(Self::Size(width.parse()?, height.parse()?)),
+            ["move", x, y] => Ok(Self::Move(x.parse()?, y.parse()?)),
+            ["workspace", workspace] => {
+                Ok(Self::Workspace(WorkspaceTarget::from(*workspace), false))
+            }
+            ["workspace", workspace, "silent"] => {
+                Ok(Self::Workspace(WorkspaceTarget::from(*workspace), true))
+            }
+            ["monitor", monitor @ ..] if !monitor.is_empty() => {
+                Ok(Self::Monitor(monitor.join(" ")))
+            }
+            [
+                "tile" | "float" | "fullscreen" | "pin" | "persistentsize" | "center" | "noanim"
+                | "blur" | "ignorezero" | "size" | "move" | "workspace" | "monitor",
+                ..,
+            ] => Err(ParseRuleError::InvalidFormat),
+            _ => Err(ParseRuleError::UnknownMode(s.to_string())),
+        };
+    }
+}
+
+pub const RULES_FILE_NAME: &str = "window-rules.conf";
+
+#[derive(Debug, Clone)]
+pub struct ConfigWrite {
+    pub path: PathBuf,
+    /// Copy of the file from before the write
+    pub backup: PathBuf,
+    pub added: Vec<String>,
+    /// Lines taken out of the file, edited lines show up here with their old text and in `added` with the new one
+    pub removed: Vec<String>,
+    /// Rules that were skipped because the file already has the exact same line
+    pub duplicates: Vec<String>,
+    /// Line numbers (starting at 1) of the lines in `added`, in the same order
+    pub added_line_numbers: Vec<usize>,
+}
+
+impl ConfigWrite {
+    pub fn added_line(&self, line_number: usize) -> Option<&String> {
+        return self
+            .added_line_numbers
+            .iter()
+            .position(|added_line_number| *added_line_number == line_number)
+            .and_then(|index| self.added.get(index));
+    }
+
+    pub fn restore_backup(&self) -> eyre::Result<()> {
+        fs::copy(&self.backup, &self.path)?;
+        return Ok(());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    pub message: String,
+    /// The line from the rules file that Hyprland is complaining about
+    pub offending_line: String,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}\n  -> {}", self.message, self.offending_line)
+    }
+}
+
+/// `rules_file` from the config, or `window-rules.conf` next to hyprland.conf
+pub fn get_rules_file() -> eyre::Result<PathBuf> {
     let hyprland_dir = system_info::get_hyprland_dir()?;
+    return Ok(match config::load_config()?.rules_file() {
+        Some(rules_file) => hyprland_dir.join(rules_file),
+        None => hyprland_dir.join(RULES_FILE_NAME),
+    });
+}
+
+/// Checks if hyprland.conf has a `source` line pointing at the rules file
+pub fn is_rules_file_sourced() -> eyre::Result<bool> {
+    let rules_file = get_rules_file()?;
+    let Some(rules_file_name) = rules_file.file_name().and_then(|name| name.to_str()) else {
+        return Ok(false);
+    };
+    let Ok(main_config) = fs::read_to_string(system_info::get_hyprland_config()?) else {
+        return Ok(false);
+    };
+    return Ok(main_config.lines().any(|line| {
+        let line = line.trim();
+        line.starts_with("source") && line.contains(rules_file_name)
+    }));
+}
+
+/// Appends `lines` under a `# header` comment to the managed rules file, lines that are already in the file are skipped
+pub fn append_to_rules_file(header: &str, lines: Vec<String>) -> eyre::Result<ConfigWrite> {
+    let rules_file = get_rules_file()?;
+    let backup = rules_file.with_extension("conf.bak");
+
+    let existing = if rules_file.exists() {
+        fs::read_to_string(&rules_file)?
+    } else {
+        String::new()
+    };
+
+    let (duplicates, added): (Vec<String>, Vec<String>) = lines
+        .into_iter()
+        .partition(|rule| existing.lines().any(|line| line.trim() == rule));
+
+    if !added.is_empty() {
+        if let Some(parent) = rules_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&backup, &existing)?;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&rules_file)?;
+        if !existing.is_empty() && !existing.ends_with('\n') {
+            writeln!(file)?;
+        }
+        writeln!(file, "# {}", header)?;
+        for rule in &added {
+            writeln!(file, "{}", rule)?;
+        }
+    }
+
+    // +1 for the header comment and +1 because line numbers start at 1
+    let first_added_line = existing.lines().count() + 2;
+    return Ok(ConfigWrite {
+        path: rules_file,
+        backup,
+        added_line_numbers: (first_added_line..first_added_line + added.len()).collect(),
+        added,
+        removed: vec![],
+        duplicates,
+    });
+}
+
+pub fn generate_workspace_config_for(rule: &WorkspaceRule) -> eyre::Result<ConfigWrite> {
+    return append_to_rules_file(
+        &format!("workspace: {}", rule.workspace),
+        vec![rule.to_string()],
+    );
+}
+
+fn get_config_errors() -> eyre::Result<Vec<String>> {
+    let output = Command::new("hyprctl")
+        .args(["-j", "configerrors"])
+        .output()?;
+    if !output.status.success() {
+        return Err(eyre::eyre!(
+            "hyprctl configerrors failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    let errors: Vec<String> = serde_json::from_slice(&output.stdout)?;
+    return Ok(errors
+        .into_iter()
+        .filter(|error| !error.trim().is_empty())
+        .collect());
+}
+
+/// Reloads Hyprland and checks if `hyprctl configerrors` has anything new to say about the file `write` changed.
+/// Any new error counts, removing a line can break the ones around it too. When there is one the backup is
+/// put back and Hyprland is reloaded again so the broken rules never stick around.
+/// Nothing is reloaded when `auto_reload` is turned off in the config or a snapshot is in use.
+pub fn reload_and_verify(write: &ConfigWrite) -> eyre::Result<Vec<ConfigError>> {
+    if write.added.is_empty() && write.removed.is_empty() || !config::load_config()?.auto_reload {
+        return Ok(vec![]);
+    }
+    if snapshot::is_active() {
+        println!("Using a snapshot, Hyprland wasn't reloaded");
+        return Ok(vec![]);
+    }
+    let error_location = Regex::new(r"in file (.+) at line (\d+)")?;
+    // lines move around when the file changes so errors from before are compared without their line number
+    let without_line = |message: &str| error_location.replace(message, "in file $1").to_string();
+    let mut known_errors: Vec<String> = get_config_errors()?
+        .iter()
+        .map(|message| without_line(message))
+        .collect();
+    reload::call()?;
+
+    let lines: Vec<String> = fs::read_to_string(&write.path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+    let errors: Vec<ConfigError> = get_config_errors()?
+        .into_iter()
+        .filter_map(|message| {
+            let location = error_location.captures(&message)?;
+            if write.path != Path::new(&location[1]) {
+                return None;
+            }
+            let line_number: usize = location[2].parse().ok()?;
+            if let Some(known) = known_errors
+                .iter()
+                .position(|known| *known == without_line(&message))
+            {
+                known_errors.remove(known);
+                return None;
+            }
+            let offending_line = match write.added_line(line_number) {
+                Some(added) => added.clone(),
+                None => line_number
+                    .checked_sub(1)
+                    .and_then(|index| lines.get(index))
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            return Some(ConfigError {
+                message,
+                offending_line,
+            });
+        })
+        .collect();
 
-    if hyprland_dir.join("").exists() {
-        // check for existing rule file for client
+    if !errors.is_empty() {
+        write.restore_backup()?;
+        reload::call()?;
     }
 
-    return Ok(());
+    return Ok(errors);
 }
@@ -0,0 +1,175 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use color_eyre::eyre;
+use hyprland::data::{Client, FullscreenMode, Monitor};
+use hyprland::dispatch::{
+    Dispatch, DispatchType, FullscreenType, MonitorIdentifier, Position, WindowIdentifier,
+    WindowMove, WorkspaceIdentifierWithSpecial,
+};
+
+use crate::hyprland_config::{Length, WindowMode, WindowRule, WorkspaceTarget};
+use crate::snapshot;
+use crate::window_geometry;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyOutcome {
+    Applied,
+    /// The window is already in the state the rule would put it in
+    AlreadyApplied,
+    /// There is no dispatcher that does the same thing as the rule
+    Unsupported,
+}
+
+impl Display for ApplyOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Applied => write!(f, "applied"),
+            Self::AlreadyApplied => write!(f, "already in effect"),
+            Self::Unsupported => write!(f, "no dispatcher equivalent, skipped"),
+        }
+    }
+}
+
+/// The connector name `selector` from a monitor rule stands for. `desc:` is looked up among the
+/// connected monitors by the start of their description like rules match it, `None` when none do
+fn monitor_name(selector: &str) -> eyre::Result<Option<String>> {
+    let Some(description) = selector.strip_prefix("desc:") else {
+        return Ok(Some(selector.to_string()));
+    };
+    return Ok(snapshot::get_monitors()?
+        .into_iter()
+        .find(|monitor| monitor.description.starts_with(description))
+        .map(|monitor| monitor.name));
+}
+
+/// Dispatchers take pixels as `i16`, a layout that big or a broken rule is an error rather than
+/// a window somewhere else. `what` says which value it was.
+fn to_i16(value: i32, what: &str) -> eyre::Result<i16> {
+    return i16::try_from(value).map_err(|_| {
+        eyre::eyre!(
+            "The {} of {} pixels is too big for a dispatcher",
+            what,
+            value
+        )
+    });
+}
+
+/// Runs the dispatchers that have the same effect as `rule` on the window at `client.address`.
+///
+/// Most of the dispatchers toggle state so the current state of `client` is checked first,
+/// applying a float rule to an already floating window would otherwise tile it.
+/// `monitor` is the monitor the window is on, size and move rules are relative to it. Without it
+/// percentages can't be worked out and moves have nothing to start from, those rules are skipped
+/// as unsupported.
+pub fn apply_rule(
+    client: &Client,
+    monitor: Option<&Monitor>,
+    rule: &WindowRule,
+) -> eyre::Result<ApplyOutcome> {
+    let window = || WindowIdentifier::Address(client.address.clone());
+    let monitor_size = monitor.map(window_geometry::monitor_logical_size);
+    let (monitor_x, monitor_y) = monitor
+        .map(|monitor| (monitor.x, monitor.y))
+        .unwrap_or((0, 0));
+    let to_pixels = |x: Length, y: Length| -> Option<(i32, i32)> {
+        return match (monitor_size, x, y) {
+            (Some((width, height)), x, y) => Some((
+                window_geometry::to_pixels(x, width),
+                window_geometry::to_pixels(y, height),
+            )),
+            (None, Length::Pixels(x), Length::Pixels(y)) => Some((x, y)),
+            (None, _, _) => None,
+        };
+    };
+
+    let target_monitor = match &rule.mode {
+        WindowMode::Monitor(selector) => monitor_name(selector)?,
+        _ => None,
+    };
+
+    let dispatches = match rule.mode {
+        WindowMode::Float if client.floating => return Ok(ApplyOutcome::AlreadyApplied),
+        WindowMode::Float => vec![DispatchType::ToggleFloating(Some(window()))],
+        WindowMode::Tile if !client.floating => return Ok(ApplyOutcome::AlreadyApplied),
+        WindowMode::Tile => vec![DispatchType::ToggleFloating(Some(window()))],
+        WindowMode::Pin if client.pinned => return Ok(ApplyOutcome::AlreadyApplied),
+        WindowMode::Pin => vec![DispatchType::TogglePinWindow(window())],
+        WindowMode::Fullscreen if client.fullscreen != FullscreenMode::None => {
+            return Ok(ApplyOutcome::AlreadyApplied);
+        }
+        // the fullscreen dispatcher only works on the active window
+        WindowMode::Fullscreen => vec![
+            DispatchType::FocusWindow(window()),
+            DispatchType::ToggleFullscreen(FullscreenType::Real),
+        ],
+        WindowMode::PersistentSize
+        | WindowMode::NoAnim
+        | WindowMode::Blur
+        | WindowMode::IgnoreZero => return Ok(ApplyOutcome::Unsupported),
+        WindowMode::Size(width, height) => {
+            let Some((width, height)) = to_pixels(width, height) else {
+                return Ok(ApplyOutcome::Unsupported);
+            };
+            vec![DispatchType::ResizeWindowPixel(
+                Position::Exact(to_i16(width, "width")?, to_i16(height, "height")?),
+                window(),
+            )]
+        }
+        WindowMode::Move(x, y) => {
+            // moves are relative to the monitor, without it the window would end up somewhere else
+            if monitor.is_none() {
+                println!("Couldn't find the window's monitor, skipping move");
+                return Ok(ApplyOutcome::Unsupported);
+            }
+            let Some((x, y)) = to_pixels(x, y) else {
+                return Ok(ApplyOutcome::Unsupported);
+            };
+            vec![DispatchType::MoveWindowPixel(
+                Position::Exact(
+                    to_i16(x + monitor_x, "x position")?,
+                    to_i16(y + monitor_y, "y position")?,
+                ),
+                window(),
+            )]
+        }
+        // like fullscreen centering only works on the active window
+        WindowMode::Center => vec![
+            DispatchType::FocusWindow(window()),
+            DispatchType::CenterWindow,
+        ],
+        WindowMode::Workspace(ref target, silent) => {
+            let workspace = match target {
+                WorkspaceTarget::Id(id) => WorkspaceIdentifierWithSpecial::Id(*id),
+                WorkspaceTarget::Name(name) => WorkspaceIdentifierWithSpecial::Name(name),
+                WorkspaceTarget::Special(name) => {
+                    WorkspaceIdentifierWithSpecial::Special(name.as_deref())
+                }
+            };
+            if silent {
+                vec![DispatchType::MoveToWorkspaceSilent(
+                    workspace,
+                    Some(window()),
+                )]
+            } else {
+                vec![DispatchType::MoveToWorkspace(workspace, Some(window()))]
+            }
+        }
+        // movewindow only moves the active window
+        WindowMode::Monitor(_) => {
+            // the monitor the rule is for isn't connected
+            let Some(name) = &target_monitor else {
+                return Ok(ApplyOutcome::Unsupported);
+            };
+            vec![
+                DispatchType::FocusWindow(window()),
+                DispatchType::MoveWindow(WindowMove::Monitor(MonitorIdentifier::Name(name))),
+            ]
+        }
+    };
+
+    for dispatch in dispatches {
+        Dispatch::call(dispatch)?;
+    }
+
+    return Ok(ApplyOutcome::Applied);
+}
@@ -0,0 +1,51 @@
+use color_eyre::eyre;
+use hyprland::data::LayerClient;
+
+use crate::snapshot;
+
+/// Namespace of the layer surface drawn by the window selector
+pub const SELECTOR_NAMESPACE: &str = "selection";
+
+#[derive(Debug, Clone)]
+pub struct LayerSurface {
+    /// Connector name of the monitor the layer is on
+    pub monitor: String,
+    /// 0 background, 1 bottom, 2 top, 3 overlay
+    pub level: u8,
+    pub layer: LayerClient,
+}
+
+impl LayerSurface {
+    /// Top and overlay layers are drawn over windows, background and bottom ones under them
+    pub fn is_above_windows(&self) -> bool {
+        return self.level >= 2;
+    }
+}
+
+/// Every layer surface on every monitor, flattened out of the monitor -> level -> layers map hyprctl gives us
+pub fn get_layer_surfaces() -> eyre::Result<Vec<LayerSurface>> {
+    let layers = snapshot::get_layers()?;
+
+    let mut layer_surfaces: Vec<LayerSurface> = layers
+        .iter()
+        .flat_map(|(monitor, display)| {
+            display.iter().flat_map(move |(level, layers)| {
+                let level = level.parse::<u8>().unwrap_or_default();
+                layers.iter().map(move |layer| LayerSurface {
+                    monitor: monitor.clone(),
+                    level,
+                    layer: layer.clone(),
+                })
+            })
+        })
+        .filter(|layer_surface| layer_surface.layer.namespace != SELECTOR_NAMESPACE)
+        .collect();
+    // hash map order is random, keep the picker stable and check the top most layers first
+    layer_surfaces.sort_by(|a, b| {
+        b.level
+            .cmp(&a.level)
+            .then_with(|| a.layer.namespace.cmp(&b.layer.namespace))
+    });
+
+    return Ok(layer_surfaces);
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre;
+use hyprland::data::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::hyprland_config::ConfigWrite;
+use crate::line_diff::{self, Hunk};
+use crate::system_info;
+
+pub const JOURNAL_FILE_NAME: &str = "journal.jsonl";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalAction {
+    Write,
+    Edit,
+    /// Reverted the entry with this id
+    Undo(u64),
+}
+
+impl Display for JournalAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Write => write!(f, "write"),
+            Self::Edit => write!(f, "edit"),
+            Self::Undo(id) => write!(f, "undo #{}", id),
+        }
+    }
+}
+
+/// What the rules were for, `window` is what the picked window looked like at the time
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Target {
+    pub label: String,
+    pub window: Option<Client>,
+}
+
+impl From<&Client> for Target {
+    fn from(client: &Client) -> Self {
+        return Self {
+            label: format!("{}: {}", client.class, client.title),
+            window: Some(client.clone()),
+        };
+    }
+}
+
+impl From<String> for Target {
+    fn from(label: String) -> Self {
+        return Self {
+            label,
+            window: None,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    pub id: u64,
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub action: JournalAction,
+    pub target: Target,
+    pub path: PathBuf,
+    pub hunks: Vec<Hunk>,
+}
+
+impl JournalEntry {
+    pub fn added_count(&self) -> usize {
+        return self.hunks.iter().map(|hunk| hunk.added.len()).sum();
+    }
+
+    pub fn removed_count(&self) -> usize {
+        return self.hunks.iter().map(|hunk| hunk.removed.len()).sum();
+    }
+}
+
+pub fn get_journal_file() -> eyre::Result<PathBuf> {
+    return Ok(system_info::get_data_dir()?.join(JOURNAL_FILE_NAME));
+}
+
+pub fn read_journal() -> eyre::Result<Vec<JournalEntry>> {
+    let journal_file = get_journal_file()?;
+    if !journal_file.exists() {
+        return Ok(vec![]);
+    }
+    return fs::read_to_string(journal_file)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect();
+}
+
+/// Adds the change `written` made to the journal, call it once Hyprland has accepted the change
+pub fn record(
+    action: JournalAction,
+    target: &Target,
+    written: &ConfigWrite,
+) -> eyre::Result<Option<JournalEntry>> {
+    // nothing was written so the backup isn't from this write
+    if written.added.is_empty() && written.removed.is_empty() {
+        return Ok(None);
+    }
+    let before = fs::read_to_string(&written.backup)?;
+    let after = fs::read_to_string(&written.path)?;
+    let hunks = line_diff::diff_lines(&before, &after);
+    if hunks.is_empty() {
+        return Ok(None);
+    }
+
+    let entry = JournalEntry {
+        id: read_journal()?
+            .last()
+            .map(|entry| entry.id + 1)
+            .unwrap_or(1),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        action,
+        target: target.clone(),
+        path: written.path.clone(),
+        hunks,
+    };
+
+    let journal_file = get_journal_file()?;
+    if let Some(parent) = journal_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(journal_file)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+    return Ok(Some(entry));
+}
+
+/// Passes on what reloading after `written` returned. When reloading failed the file was still
+/// changed, so the change is recorded first to keep it undoable
+pub fn record_if_failed<T>(
+    reloaded: eyre::Result<T>,
+    action: JournalAction,
+    target: &Target,
+    written: &ConfigWrite,
+) -> eyre::Result<T> {
+    if reloaded.is_err() {
+        record(action, target, written)?;
+    }
+    return reloaded;
+}
+
+/// Ids of entries that have been undone
+pub fn undone_ids(entries: &[JournalEntry]) -> HashSet<u64> {
+    return entries
+        .iter()
+        .filter_map(|entry| match entry.action {
+            JournalAction::Undo(id) => Some(id),
+            _ => None,
+        })
+        .collect();
+}
+
+/// The last `count` entries that haven't been undone yet, newest first. Undos themselves are
+/// skipped, undoing twice takes back two changes rather than putting the first one back
+pub fn undoable(entries: &[JournalEntry], count: usize) -> Vec<JournalEntry> {
+    let undone = undone_ids(entries);
+    return entries
+        .iter()
+        .rev()
+        .filter(|entry| !matches!(entry.action, JournalAction::Undo(_)))
+        .filter(|entry| !undone.contains(&entry.id))
+        .take(count)
+        .cloned()
+        .collect();
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, action: JournalAction) -> JournalEntry {
+        return JournalEntry {
+            id,
+            timestamp: 0,
+            action,
+            target: Target::from(format!("entry {}", id)),
+            path: PathBuf::from("rules.conf"),
+            hunks: vec![],
+        };
+    }
+
+    fn ids(entries: &[JournalEntry]) -> Vec<u64> {
+        return entries.iter().map(|entry| entry.id).collect();
+    }
+
+    #[test]
+    fn undoable_skips_undos_and_what_they_undid() {
+        let entries = [
+            entry(1, JournalAction::Write),
+            entry(2, JournalAction::Edit),
+            entry(3, JournalAction::Write),
+            entry(4, JournalAction::Undo(3)),
+        ];
+        assert_eq!(ids(&undoable(&entries, 1)), vec![2]);
+        assert_eq!(ids(&undoable(&entries, 5)), vec![2, 1]);
+    }
+}
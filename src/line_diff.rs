@@ -0,0 +1,177 @@
+use color_eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+/// A run of changed lines along with the unchanged lines around it, which are used to find
+/// where the change is again after the file has been edited by hand
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Line number (starting at 1) of the first removed line in the old file, or the line the new ones went in front of
+    pub old_line: usize,
+    /// Same as `old_line` but in the new file
+    pub new_line: usize,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+}
+
+enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Plain longest common subsequence diff, rules files are small enough that the quadratic table doesn't matter
+pub fn diff_lines(old: &str, new: &str) -> Vec<Hunk> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push(Op::Keep);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            ops.push(Op::Add);
+            j += 1;
+        } else {
+            ops.push(Op::Remove);
+            i += 1;
+        }
+    }
+
+    let mut hunks: Vec<Hunk> = vec![];
+    let (mut i, mut j) = (0, 0);
+    let mut in_hunk = false;
+    for op in ops {
+        match op {
+            Op::Keep => {
+                if in_hunk && let Some(hunk) = hunks.last_mut() {
+                    hunk.after = Some(old[i].to_string());
+                }
+                in_hunk = false;
+                i += 1;
+                j += 1;
+            }
+            Op::Remove | Op::Add => {
+                if !in_hunk {
+                    hunks.push(Hunk {
+                        old_line: i + 1,
+                        new_line: j + 1,
+                        before: i.checked_sub(1).map(|before| old[before].to_string()),
+                        after: None,
+                        removed: vec![],
+                        added: vec![],
+                    });
+                    in_hunk = true;
+                }
+                let hunk = hunks.last_mut().expect("hunk was just pushed");
+                if let Op::Remove = op {
+                    hunk.removed.push(old[i].to_string());
+                    i += 1;
+                } else {
+                    hunk.added.push(new[j].to_string());
+                    j += 1;
+                }
+            }
+        }
+    }
+
+    return hunks;
+}
+
+/// Where `hunk` is in `lines`, when there is more than one place the one closest to where it was is used
+fn find_hunk(lines: &[String], hunk: &Hunk) -> Option<usize> {
+    let matches_at = |index: usize| -> bool {
+        if !hunk.added.is_empty() {
+            return lines
+                .get(index..index + hunk.added.len())
+                .is_some_and(|found| found == hunk.added.as_slice());
+        }
+        // a hunk at the start or the end of the file only has context on one side
+        let before = match index.checked_sub(1) {
+            Some(before) => lines.get(before),
+            None => None,
+        };
+        let before_matches = hunk.before.is_none() || before == hunk.before.as_ref();
+        let after_matches = hunk.after.is_none() || lines.get(index) == hunk.after.as_ref();
+        return before_matches && after_matches;
+    };
+    let expected = hunk.new_line.saturating_sub(1);
+
+    return (0..=lines.len())
+        .filter(|index| matches_at(*index))
+        .min_by_key(|index| index.abs_diff(expected));
+}
+
+/// Takes the changes in `hunks` back out of `current`, lines the hunks don't touch can have changed in the mean time
+pub fn revert_hunks(current: &str, hunks: &[Hunk]) -> eyre::Result<String> {
+    let mut lines: Vec<String> = current.lines().map(|line| line.to_string()).collect();
+
+    // going from the bottom up keeps the expected line numbers of the hunks above valid
+    for hunk in hunks.iter().rev() {
+        let Some(index) = find_hunk(&lines, hunk) else {
+            return Err(eyre::eyre!(
+                "Couldn't find the change at line {} anymore, it was edited after it was made",
+                hunk.new_line
+            ));
+        };
+        lines.splice(
+            index..index + hunk.added.len(),
+            hunk.removed.iter().cloned(),
+        );
+    }
+
+    let mut reverted = lines.join("\n");
+    if !lines.is_empty() {
+        reverted.push('\n');
+    }
+    return Ok(reverted);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        return text.lines().map(|line| line.to_string()).collect();
+    }
+
+    #[test]
+    fn removal_needs_the_lines_on_both_sides() {
+        let hunks = diff_lines("a\nb\nc\n", "a\nc\n");
+        assert_eq!(hunks.len(), 1);
+        // `a` is followed by something else at the top, only the second `a` still has `c` after it
+        let current = lines("a\nx\nother\na\nc\n");
+        assert_eq!(find_hunk(&current, &hunks[0]), Some(4));
+        assert_eq!(
+            revert_hunks("a\nx\nother\na\nc\n", &hunks).unwrap(),
+            "a\nx\nother\na\nb\nc\n"
+        );
+    }
+
+    #[test]
+    fn removal_at_the_end_only_needs_the_line_before() {
+        let hunks = diff_lines("a\nb\n", "a\n");
+        assert_eq!(revert_hunks("a\n", &hunks).unwrap(), "a\nb\n");
+    }
+
+    #[test]
+    fn removal_without_its_context_is_not_found() {
+        let hunks = diff_lines("a\nb\nc\n", "a\nc\n");
+        assert!(revert_hunks("a\nx\n", &hunks).is_err());
+    }
+}
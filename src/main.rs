@@ -1,28 +1,54 @@
+mod app_info;
+mod config;
+mod desktop_entry;
+mod event_log;
 mod hyprland_config;
+mod hyprland_dispatch;
+mod hyprland_layers;
+mod journal;
+mod line_diff;
+mod matcher_suggestion;
+mod notification;
+mod process_info;
+mod rule_simulation;
+mod rule_templates;
+mod rules_file;
 mod shell_command;
+mod snapshot;
 mod system_info;
+mod title_normalizer;
 mod tui;
 mod wayland;
-
-use std::time::Duration;
+mod window_events;
+mod window_geometry;
 
 use shell_command::commands::options_exec;
 use shell_command::types::*;
 
 use color_eyre::{Result, eyre};
 use eyre::Error;
-use ratatui::{
-    DefaultTerminal, Frame,
-    crossterm::event::{self, Event, KeyCode},
-    widgets::Paragraph,
-};
 
 use clap::Parser;
 
 fn main() -> Result<()> {
-    system_info::get_data_dir();
     //window_selector::create_window();
     let cli = Cli::parse();
+    if let Some(hyprland_config) = &cli.config {
+        system_info::set_hyprland_config(hyprland_config.clone());
+    }
+    // the old directories are only a convenience, they shouldn't stop anything from running.
+    // The clipboard server is a copy of ourselves started by a command that already moved them.
+    if !matches!(cli.command, Some(Commands::ServeClipboard))
+        && let Err(error) = system_info::migrate_legacy_dirs()
+    {
+        eprintln!(
+            "Couldn't move the old data and config directories over: {}",
+            error
+        );
+    }
+    if let Some(name) = &cli.snapshot {
+        snapshot::use_snapshot(name)?;
+    }
 
     // You can check the value provided by positional arguments, or option arguments
     if let Some(name) = cli.name.as_deref() {
@@ -36,18 +62,35 @@ fn main() -> Result<()> {
     // You can check for the existence of subcommands, and if found use their
     // matches just as you would the top level cmd
     let cmd_result = match &cli.command {
-        Some(Commands::Generate {
-            float,
-            persistentsize,
-            tile,
-            fullscreen,
-        }) => {
-            return shell_command::commands::generate::exec(
-                float.clone(),
-                persistentsize.clone(),
-                tile.clone(),
-                fullscreen.clone(),
-            );
+        Some(Commands::Generate(args)) => {
+            return shell_command::commands::generate::exec(args);
+        }
+        Some(Commands::Workspace(args)) => {
+            return shell_command::commands::workspace::exec(args);
+        }
+        Some(Commands::Remove(args)) => {
+            return shell_command::commands::remove::exec(args);
+        }
+        Some(Commands::Edit(args)) => {
+            return shell_command::commands::edit::exec(args);
+        }
+        Some(Commands::History) => {
+            return shell_command::commands::history::exec();
+        }
+        Some(Commands::Undo(args)) => {
+            return shell_command::commands::undo::exec(args);
+        }
+        Some(Commands::Monitor(args)) => {
+            return shell_command::commands::monitor::exec(args);
+        }
+        Some(Commands::Simulate(args)) => {
+            return shell_command::commands::simulate::exec(args);
+        }
+        Some(Commands::Snapshot(args)) => {
+            return shell_command::commands::snapshot::exec(args);
+        }
+        Some(Commands::ServeClipboard) => {
+            return shell_command::commands::serve_clipboard::exec();
         }
         None => Err(Error::msg("Unknown option")),
     };
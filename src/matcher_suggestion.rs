@@ -0,0 +1,378 @@
+use hyprland::data::Client;
+
+use crate::hyprland_config::Parameter;
+use crate::rules_file;
+use crate::title_normalizer::escape_regex;
+
+#[derive(Debug, Clone)]
+pub struct MatcherSuggestion {
+    pub parameters: Vec<Parameter>,
+    /// Other open windows the parameters match too
+    pub also_matches: Vec<Client>,
+}
+
+fn parameter_matches(parameter: &Parameter, client: &Client) -> bool {
+    let (pattern, value) = match parameter {
+        Parameter::Class(pattern) => (pattern, &client.class),
+        Parameter::Title(pattern) => (pattern, &client.title),
+        Parameter::InitialClass(pattern) => (pattern, &client.initial_class),
+        Parameter::InitialTitle(pattern) => (pattern, &client.initial_title),
+        Parameter::Namespace(_) => return false,
+    };
+    return rules_file::full_match(pattern, value);
+}
+
+/// Open windows that aren't in `selected` that every one of `parameters` matches
+fn other_matches(parameters: &[Parameter], selected: &[Client], clients: &[Client]) -> Vec<Client> {
+    return clients
+        .iter()
+        .filter(|other| {
+            selected
+                .iter()
+                .all(|client| client.address != other.address)
+        })
+        .filter(|other| {
+            parameters
+                .iter()
+                .all(|parameter| parameter_matches(parameter, other))
+        })
+        .cloned()
+        .collect();
+}
+
+/// Picks the least specific matcher that only hits `client` out of the open `clients`, a unique
+/// `initialClass` on its own comes first since it doesn't change, then the class with a title
+/// pattern, then the exact title. When everything hits other windows the one hitting the fewest is used.
+pub fn suggest_matcher(client: &Client, clients: &[Client]) -> MatcherSuggestion {
+    let class = (!client.initial_class.is_empty())
+        .then(|| Parameter::InitialClass(escape_regex(&client.initial_class)));
+    let with_class = |title: Parameter| -> Vec<Parameter> {
+        return class.iter().cloned().chain([title]).collect();
+    };
+
+    let mut candidates: Vec<Vec<Parameter>> = vec![];
+    if let Some(class) = &class {
+        candidates.push(vec![class.clone()]);
+    }
+    candidates.extend([
+        with_class(Parameter::initial_title_of(client)),
+        with_class(Parameter::title_of(client)),
+        with_class(Parameter::InitialTitle(escape_regex(&client.initial_title))),
+        with_class(Parameter::Title(escape_regex(&client.title))),
+    ]);
+    candidates.dedup();
+
+    return first_unique(candidates, std::slice::from_ref(client), clients);
+}
+
+/// The first of `candidates` that matches nothing open outside of `selected`, or the one that
+/// matches the fewest other windows
+fn first_unique(
+    candidates: Vec<Vec<Parameter>>,
+    selected: &[Client],
+    clients: &[Client],
+) -> MatcherSuggestion {
+    let mut best: Option<MatcherSuggestion> = None;
+    for parameters in candidates {
+        let also_matches = other_matches(&parameters, selected, clients);
+        if also_matches.is_empty() {
+            return MatcherSuggestion {
+                parameters,
+                also_matches,
+            };
+        }
+        if best
+            .as_ref()
+            .is_none_or(|best| also_matches.len() < best.also_matches.len())
+        {
+            best = Some(MatcherSuggestion {
+                parameters,
+                also_matches,
+            });
+        }
+    }
+    return best.expect("there is always at least one candidate");
+}
+
+/// `(a|b)` over the distinct values, or just the value when they're all the same
+fn alternation<'a>(values: impl Iterator<Item = &'a String>) -> String {
+    let mut escaped: Vec<String> = vec![];
+    for value in values {
+        let value = escape_regex(value);
+        if !escaped.contains(&value) {
+            escaped.push(value);
+        }
+    }
+    if escaped.len() == 1 {
+        return escaped.remove(0);
+    }
+    return format!("({})", escaped.join("|"));
+}
+
+/// What all `values` start with as a pattern, a couple of characters isn't enough to go on
+fn common_prefix<'a>(mut values: impl Iterator<Item = &'a String>) -> Option<String> {
+    let first = values.next()?;
+    let mut prefix: &str = first;
+    let mut all_equal = true;
+    for value in values {
+        all_equal &= value == first;
+        let length = prefix
+            .char_indices()
+            .zip(value.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((index, a), _)| index + a.len_utf8());
+        prefix = &prefix[..length];
+    }
+    if all_equal {
+        return Some(escape_regex(first));
+    }
+    if prefix.trim().chars().count() < 3 {
+        return None;
+    }
+    return Some(format!("{}.*", escape_regex(prefix)));
+}
+
+/// One matcher for all of `selected` that nothing else open matches, least specific first: the
+/// classes on their own, then a common title prefix, then both, then the classes with the exact titles
+pub fn suggest_combined_matcher(selected: &[Client], clients: &[Client]) -> MatcherSuggestion {
+    let class = Parameter::InitialClass(alternation(
+        selected.iter().map(|client| &client.initial_class),
+    ));
+    let title_prefixes: Vec<Parameter> = [
+        common_prefix(selected.iter().map(|client| &client.initial_title))
+            .map(Parameter::InitialTitle),
+        common_prefix(selected.iter().map(|client| &client.title)).map(Parameter::Title),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    let mut candidates: Vec<Vec<Parameter>> = vec![vec![class.clone()]];
+    candidates.extend(title_prefixes.iter().map(|title| vec![title.clone()]));
+    candidates.extend(
+        title_prefixes
+            .iter()
+            .map(|title| vec![class.clone(), title.clone()]),
+    );
+    candidates.push(vec![
+        class,
+        Parameter::InitialTitle(alternation(
+            selected.iter().map(|client| &client.initial_title),
+        )),
+    ]);
+    return first_unique(candidates, selected, clients);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyprland::event_listener::WindowOpenEvent;
+    use hyprland::shared::Address;
+
+    use crate::window_events;
+
+    fn client(address: &str, class: &str, title: &str) -> Client {
+        return window_events::client_from_event(&WindowOpenEvent {
+            window_address: Address::new(address),
+            workspace_name: "1".to_string(),
+            window_class: class.to_string(),
+            window_title: title.to_string(),
+        });
+    }
+
+    fn matches(parameters: &[Parameter], client: &Client) -> bool {
+        return parameters
+            .iter()
+            .all(|parameter| parameter_matches(parameter, client));
+    }
+
+    #[test]
+    fn a_unique_class_is_enough() {
+        let clients = [
+            client("1", "org.gnome.Nautilus", "Downloads"),
+            client("2", "foot", "~"),
+        ];
+
+        let suggestion = suggest_matcher(&clients[0], &clients);
+
+        assert_eq!(
+            suggestion.parameters,
+            [Parameter::InitialClass(r"org\.gnome\.Nautilus".to_string())]
+        );
+        assert!(suggestion.also_matches.is_empty());
+        assert!(matches(&suggestion.parameters, &clients[0]));
+        assert!(!matches(&suggestion.parameters, &clients[1]));
+    }
+
+    #[test]
+    fn a_shared_class_falls_back_to_the_title() {
+        let clients = [
+            client("1", "firefox", "Inbox - Mozilla Firefox"),
+            client("2", "firefox", "(3) Chat - Mozilla Firefox"),
+            client("3", "foot", "Inbox - Mozilla Firefox"),
+        ];
+
+        let suggestion = suggest_matcher(&clients[0], &clients);
+
+        assert_eq!(
+            suggestion.parameters,
+            [
+                Parameter::InitialClass("firefox".to_string()),
+                Parameter::InitialTitle(r"Inbox.*".to_string()),
+            ]
+        );
+        assert!(suggestion.also_matches.is_empty());
+        assert!(matches(&suggestion.parameters, &clients[0]));
+        assert!(!matches(&suggestion.parameters, &clients[1]));
+        assert!(!matches(&suggestion.parameters, &clients[2]));
+    }
+
+    #[test]
+    fn without_a_unique_matcher_the_one_hitting_the_fewest_windows_is_used() {
+        let clients = [
+            client("1", "foot", "~"),
+            client("2", "foot", "~"),
+            client("3", "foot", "htop"),
+        ];
+
+        let suggestion = suggest_matcher(&clients[0], &clients);
+
+        assert!(matches(&suggestion.parameters, &clients[0]));
+        let also_matches: Vec<&Address> = suggestion
+            .also_matches
+            .iter()
+            .map(|client| &client.address)
+            .collect();
+        assert_eq!(also_matches, [&clients[1].address]);
+    }
+
+    #[test]
+    fn first_unique_prefers_the_first_candidate_that_hits_nothing_else() {
+        let clients = [client("1", "foot", "~"), client("2", "foot", "htop")];
+        let class = vec![Parameter::Class("foot".to_string())];
+        let title = vec![Parameter::Title("~".to_string())];
+        let anything = vec![Parameter::Title(".*".to_string())];
+
+        let suggestion = first_unique(
+            vec![class.clone(), title.clone(), anything.clone()],
+            &clients[..1],
+            &clients,
+        );
+        assert_eq!(suggestion.parameters, title);
+
+        let suggestion = first_unique(vec![anything, class.clone()], &clients[..1], &clients);
+        // both hit the other window, the first of them is kept
+        assert_eq!(suggestion.parameters, [Parameter::Title(".*".to_string())]);
+        assert_eq!(suggestion.also_matches.len(), 1);
+    }
+
+    #[test]
+    fn alternation_escapes_and_dedups_the_values() {
+        let values = ["a.b".to_string(), "c|d".to_string(), "a.b".to_string()];
+        assert_eq!(alternation(values.iter()), r"(a\.b|c\|d)");
+        assert_eq!(alternation(values[..1].iter()), r"a\.b");
+    }
+
+    #[test]
+    fn common_prefix_needs_a_few_characters() {
+        let titles = [
+            "Report (draft).odt".to_string(),
+            "Report (final).odt".to_string(),
+        ];
+        assert_eq!(
+            common_prefix(titles.iter()),
+            Some(r"Report \(.*".to_string())
+        );
+        let titles = ["ab one".to_string(), "ab two".to_string()];
+        assert_eq!(common_prefix(titles.iter()), None);
+        let titles = ["same?".to_string(), "same?".to_string()];
+        assert_eq!(common_prefix(titles.iter()), Some(r"same\?".to_string()));
+    }
+
+    /// The suggestion hits every one of `selected` and nothing else open
+    fn assert_only_selected(
+        suggestion: &MatcherSuggestion,
+        selected: &[Client],
+        clients: &[Client],
+    ) {
+        assert!(
+            suggestion.also_matches.is_empty(),
+            "{:?}",
+            suggestion.parameters
+        );
+        for client in clients {
+            let is_selected = selected
+                .iter()
+                .any(|selected| selected.address == client.address);
+            assert_eq!(
+                matches(&suggestion.parameters, client),
+                is_selected,
+                "{:?} on {}",
+                suggestion.parameters,
+                client.title
+            );
+        }
+    }
+
+    #[test]
+    fn combined_matcher_alternates_escaped_classes() {
+        let clients = [
+            client("1", "org.gnome.Nautilus", "Files"),
+            client("2", "c++ ide", "main.cpp"),
+            client("3", "orgXgnomeXNautilus", "Files"),
+            client("4", "c ide", "main.cpp"),
+        ];
+        let selected = &clients[..2];
+
+        let suggestion = suggest_combined_matcher(selected, &clients);
+
+        assert_eq!(
+            suggestion.parameters,
+            [Parameter::InitialClass(
+                r"(org\.gnome\.Nautilus|c\+\+ ide)".to_string()
+            )]
+        );
+        assert_only_selected(&suggestion, selected, &clients);
+    }
+
+    #[test]
+    fn combined_matcher_uses_a_common_title_prefix_when_the_class_is_shared() {
+        let clients = [
+            client("1", "libreoffice", "Report (draft).odt"),
+            client("2", "libreoffice", "Report (final).odt"),
+            client("3", "libreoffice", "Budget.ods"),
+            client("4", "foot", "~"),
+        ];
+        let selected = &clients[..2];
+
+        let suggestion = suggest_combined_matcher(selected, &clients);
+
+        assert_eq!(
+            suggestion.parameters,
+            [Parameter::InitialTitle(r"Report \(.*".to_string())]
+        );
+        assert_only_selected(&suggestion, selected, &clients);
+    }
+
+    #[test]
+    fn combined_matcher_falls_back_to_the_exact_titles() {
+        let clients = [
+            client("1", "foot", "htop"),
+            client("2", "foot", "vim (1)"),
+            client("3", "foot", "~"),
+        ];
+        let selected = &clients[..2];
+
+        let suggestion = suggest_combined_matcher(selected, &clients);
+
+        assert_eq!(
+            suggestion.parameters,
+            [
+                Parameter::InitialClass("foot".to_string()),
+                Parameter::InitialTitle(r"(htop|vim \(1\))".to_string()),
+            ]
+        );
+        assert_only_selected(&suggestion, selected, &clients);
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::Value;
+use zbus::{MatchRule, Message};
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// A button on the notification, `key` is what `ask` returns when it's clicked
+pub struct NotificationAction {
+    pub key: &'static str,
+    pub label: &'static str,
+}
+
+/// Sends the notification to the notification daemon, returns its id
+fn send(
+    connection: &Connection,
+    summary: &str,
+    body: &str,
+    actions: &[NotificationAction],
+) -> eyre::Result<u32> {
+    let actions: Vec<&str> = actions
+        .iter()
+        .flat_map(|action| [action.key, action.label])
+        .collect();
+    let hints: HashMap<&str, Value> = HashMap::new();
+    let reply = connection
+        .call_method(
+            Some(DESTINATION),
+            OBJECT_PATH,
+            Some(INTERFACE),
+            "Notify",
+            &(
+                env!("CARGO_PKG_NAME"),
+                0u32,
+                "",
+                summary,
+                body,
+                actions,
+                hints,
+                // never expire, the user decides what happens to it
+                0i32,
+            ),
+        )
+        .map_err(|error| eyre::eyre!("Couldn't send the notification: {}", error))?;
+    return Ok(reply.body().deserialize()?);
+}
+
+fn notify_on(connection: &Connection, summary: &str, body: &str) -> eyre::Result<()> {
+    send(connection, summary, body, &[])?;
+    return Ok(());
+}
+
+/// Shows a notification without buttons
+pub fn notify(summary: &str, body: &str) -> eyre::Result<()> {
+    return notify_on(&Connection::session()?, summary, body);
+}
+
+/// What a signal from the notification daemon means for the notification with `id`, `None` when
+/// it's about something else
+fn answer(
+    message: &Message,
+    id: u32,
+    actions: &[NotificationAction],
+) -> Option<Option<&'static str>> {
+    let header = message.header();
+    match header.member()?.as_str() {
+        "ActionInvoked" => {
+            let (invoked_id, key): (u32, String) = message.body().deserialize().ok()?;
+            if invoked_id != id {
+                return None;
+            }
+            return Some(
+                actions
+                    .iter()
+                    .find(|action| action.key == key)
+                    .map(|action| action.key),
+            );
+        }
+        "NotificationClosed" => {
+            let (closed_id, _reason): (u32, u32) = message.body().deserialize().ok()?;
+            return (closed_id == id).then_some(None);
+        }
+        _ => return None,
+    }
+}
+
+fn ask_on(
+    connection: &Connection,
+    summary: &str,
+    body: &str,
+    actions: &[NotificationAction],
+) -> eyre::Result<Option<&'static str>> {
+    // listening before sending, otherwise a quick click could be missed
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(INTERFACE)?
+        .path(OBJECT_PATH)?
+        .build();
+    let signals = MessageIterator::for_match_rule(rule, connection, None)?;
+
+    let id = send(connection, summary, body, actions)?;
+    for message in signals {
+        if let Some(answer) = answer(&message?, id, actions) {
+            return Ok(answer);
+        }
+    }
+    return Ok(None);
+}
+
+/// Shows a notification with `actions` and waits for one of them to be clicked, `None` when it's
+/// dismissed instead
+pub fn ask(
+    summary: &str,
+    body: &str,
+    actions: &[NotificationAction],
+) -> eyre::Result<Option<&'static str>> {
+    return ask_on(&Connection::session()?, summary, body, actions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+
+    /// A private session bus, killed when dropped
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    /// `None` when there's no dbus-daemon to run the test against
+    fn start_bus() -> Option<Bus> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--print-address", "--nofork"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().expect("stdout is piped"))
+            .read_line(&mut address)
+            .unwrap();
+        return Some(Bus {
+            daemon,
+            address: address.trim().to_string(),
+        });
+    }
+
+    /// What the fake notification daemon does after a notification is shown
+    #[derive(Clone, Copy)]
+    enum Response {
+        Nothing,
+        /// Clicks the action with this key
+        Click(&'static str),
+        Dismiss,
+    }
+
+    /// A notification daemon that answers every notification with `response`, sends back the
+    /// summary, body and actions of each one
+    fn fake_daemon(bus: &Bus, response: Response) -> Receiver<(String, String, Vec<String>)> {
+        let connection = zbus::blocking::connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .name(DESTINATION)
+            .unwrap()
+            .build()
+            .unwrap();
+        let rule = MatchRule::builder()
+            .msg_type(MessageType::MethodCall)
+            .interface(INTERFACE)
+            .unwrap()
+            .member("Notify")
+            .unwrap()
+            .build();
+        let calls = MessageIterator::for_match_rule(rule, &connection, None).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for (id, call) in (1u32..).zip(calls) {
+                let call = call.unwrap();
+                type Notify<'a> = (
+                    &'a str,
+                    u32,
+                    &'a str,
+                    String,
+                    String,
+                    Vec<String>,
+                    HashMap<&'a str, Value<'a>>,
+                    i32,
+                );
+                let (_, _, _, summary, body, actions, _, _): Notify =
+                    call.body().deserialize().unwrap();
+                connection.reply(&call.header(), &id).unwrap();
+                // another notification's signals must not be taken as the answer
+                connection
+                    .emit_signal(
+                        None::<()>,
+                        OBJECT_PATH,
+                        INTERFACE,
+                        "NotificationClosed",
+                        &(id + 100, 2u32),
+                    )
+                    .unwrap();
+                match response {
+                    Response::Nothing => (),
+                    Response::Click(key) => connection
+                        .emit_signal(
+                            None::<()>,
+                            OBJECT_PATH,
+                            INTERFACE,
+                            "ActionInvoked",
+                            &(id, key),
+                        )
+                        .unwrap(),
+                    Response::Dismiss => connection
+                        .emit_signal(
+                            None::<()>,
+                            OBJECT_PATH,
+                            INTERFACE,
+                            "NotificationClosed",
+                            &(id, 2u32),
+                        )
+                        .unwrap(),
+                }
+                if sender.send((summary, body, actions)).is_err() {
+                    return;
+                }
+            }
+        });
+        return receiver;
+    }
+
+    fn client(bus: &Bus) -> Connection {
+        return zbus::blocking::connection::Builder::address(bus.address.as_str())
+            .unwrap()
+            .build()
+            .unwrap();
+    }
+
+    const ACTIONS: [NotificationAction; 2] = [
+        NotificationAction {
+            key: "write",
+            label: "Write \"rules\"",
+        },
+        NotificationAction {
+            key: "copy",
+            label: "Copy",
+        },
+    ];
+
+    #[test]
+    fn notify_sends_the_text_as_is() {
+        let Some(bus) = start_bus() else {
+            eprintln!("no dbus-daemon, skipping");
+            return;
+        };
+        let shown = fake_daemon(&bus, Response::Nothing);
+
+        notify_on(
+            &client(&bus),
+            "Rules written",
+            "~/hypr/\"rules\".conf\nline 2",
+        )
+        .unwrap();
+
+        let (summary, body, actions) = shown.recv().unwrap();
+        assert_eq!(summary, "Rules written");
+        assert_eq!(body, "~/hypr/\"rules\".conf\nline 2");
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn ask_returns_the_clicked_action() {
+        let Some(bus) = start_bus() else {
+            eprintln!("no dbus-daemon, skipping");
+            return;
+        };
+        let shown = fake_daemon(&bus, Response::Click("copy"));
+
+        let answer = ask_on(&client(&bus), "Window rules", "2 rules", &ACTIONS).unwrap();
+
+        assert_eq!(answer, Some("copy"));
+        let (_, _, actions) = shown.recv().unwrap();
+        assert_eq!(actions, ["write", "Write \"rules\"", "copy", "Copy"]);
+    }
+
+    #[test]
+    fn ask_returns_nothing_when_dismissed() {
+        let Some(bus) = start_bus() else {
+            eprintln!("no dbus-daemon, skipping");
+            return;
+        };
+        let _shown = fake_daemon(&bus, Response::Dismiss);
+
+        let answer = ask_on(&client(&bus), "Window rules", "2 rules", &ACTIONS).unwrap();
+
+        assert_eq!(answer, None);
+    }
+}
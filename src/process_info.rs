@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+use serde::Serialize;
+
+pub const PROC_DIR: &str = "/proc";
+
+/// Apps that run inside a wrapper, their process is the sandboxed binary rather than what the user started
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", content = "id", rename_all = "lowercase")]
+pub enum Sandbox {
+    /// The app id like `org.mozilla.firefox`
+    Flatpak(String),
+    /// The snap name
+    Snap(String),
+}
+
+impl Sandbox {
+    /// How the app gets started from outside the sandbox
+    pub fn run_command(&self) -> String {
+        return match self {
+            Self::Flatpak(app_id) => format!("flatpak run {}", app_id),
+            Self::Snap(name) => format!("snap run {}", name),
+        };
+    }
+}
+
+/// The arguments `pid` was started with, from `<proc_dir>/<pid>/cmdline`.
+/// `proc_dir` is `PROC_DIR` except for fixture directories laid out the same way.
+pub fn command_line(proc_dir: &Path, pid: i32) -> eyre::Result<Vec<String>> {
+    if pid <= 0 {
+        return Err(eyre::eyre!("no process id"));
+    }
+    let path = proc_dir.join(pid.to_string()).join("cmdline");
+    let bytes =
+        fs::read(&path).map_err(|error| eyre::eyre!("can't read {}: {}", path.display(), error))?;
+    let args: Vec<String> = bytes
+        .split(|byte| *byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect();
+    if args.is_empty() {
+        return Err(eyre::eyre!("{} is empty", path.display()));
+    }
+    return Ok(args);
+}
+
+/// Single quotes `arg` when the shell would split or expand it
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "-_./=:,@%+".contains(char));
+    if is_plain {
+        return arg.to_string();
+    }
+    return format!("'{}'", arg.replace('\'', r"'\''"));
+}
+
+/// A command line `sh -c` runs the same way the process was started
+pub fn shell_command(args: &[String]) -> String {
+    // apps that set their own process title (chromium, electron) put it all in one argument with spaces
+    if let [title] = args
+        && title.contains(' ')
+    {
+        return title.clone();
+    }
+    return args
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<String>>()
+        .join(" ");
+}
+
+/// What `<proc_dir>/<pid>/exe` points to
+pub fn executable(proc_dir: &Path, pid: i32) -> eyre::Result<PathBuf> {
+    if pid <= 0 {
+        return Err(eyre::eyre!("no process id"));
+    }
+    let path = proc_dir.join(pid.to_string()).join("exe");
+    return fs::read_link(&path)
+        .map_err(|error| eyre::eyre!("can't read {}: {}", path.display(), error));
+}
+
+/// Flatpak and Snap start every app in a systemd scope named after it, like
+/// `app-flatpak-org.mozilla.firefox-2381.scope` and `snap.firefox.firefox-6f8f.scope`.
+/// Without cgroups the Flatpak info file and the snap mount path are checked instead.
+pub fn sandbox(proc_dir: &Path, pid: i32) -> Option<Sandbox> {
+    if pid <= 0 {
+        return None;
+    }
+    let process_dir = proc_dir.join(pid.to_string());
+    let cgroup = fs::read_to_string(process_dir.join("cgroup")).unwrap_or_default();
+    for unit in cgroup.lines().flat_map(|line| line.split('/')) {
+        if let Some(scope) = unit.strip_prefix("app-flatpak-")
+            && let Some((app_id, _)) = scope.trim_end_matches(".scope").rsplit_once('-')
+        {
+            return Some(Sandbox::Flatpak(app_id.to_string()));
+        }
+        if let Some(scope) = unit.strip_prefix("snap.")
+            && let Some(name) = scope.split('.').next()
+        {
+            return Some(Sandbox::Snap(name.to_string()));
+        }
+    }
+
+    if let Ok(info) = fs::read_to_string(process_dir.join("root/.flatpak-info"))
+        && let Some(app_id) = info
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("name="))
+    {
+        return Some(Sandbox::Flatpak(app_id.trim().to_string()));
+    }
+    let executable = executable(proc_dir, pid).ok()?;
+    let name = executable.strip_prefix("/snap").ok()?.iter().next()?;
+    return Some(Sandbox::Snap(name.to_string_lossy().to_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    const PID: i32 = 4242;
+
+    /// A fake `/proc` with one process, `files` are relative to its directory
+    fn proc_dir(files: &[(&str, &[u8])]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let process_dir = dir.path().join(PID.to_string());
+        for (name, contents) in files {
+            let path = process_dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        fs::create_dir_all(process_dir).unwrap();
+        return dir;
+    }
+
+    #[test]
+    fn command_line_splits_on_nul() {
+        let dir = proc_dir(&[("cmdline", b"/usr/bin/foot\0--title\0my term\0")]);
+        assert_eq!(
+            command_line(dir.path(), PID).unwrap(),
+            vec!["/usr/bin/foot", "--title", "my term"]
+        );
+    }
+
+    #[test]
+    fn empty_command_line_is_an_error() {
+        // kernel threads and zombies have nothing in there
+        let dir = proc_dir(&[("cmdline", b"")]);
+        assert!(command_line(dir.path(), PID).is_err());
+        assert!(command_line(dir.path(), 0).is_err());
+    }
+
+    #[test]
+    fn process_title_with_spaces_is_used_as_is() {
+        let args = vec!["/opt/app/chrome --type=renderer --lang=en".to_string()];
+        assert_eq!(
+            shell_command(&args),
+            "/opt/app/chrome --type=renderer --lang=en"
+        );
+    }
+
+    #[test]
+    fn shell_command_quotes_what_the_shell_would_split() {
+        let args: Vec<String> = ["mpv", "--title=a b", "it's.mkv", ""]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(shell_command(&args), r"mpv '--title=a b' 'it'\''s.mkv' ''");
+    }
+
+    #[test]
+    fn shell_quote_leaves_plain_arguments_alone() {
+        assert_eq!(shell_quote("--size=80x24"), "--size=80x24");
+        assert_eq!(shell_quote("~/file"), "'~/file'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+    }
+
+    #[test]
+    fn flatpak_scope_in_cgroup() {
+        let dir = proc_dir(&[(
+            "cgroup",
+            b"0::/user.slice/user-1000.slice/user@1000.service/app.slice/app-flatpak-org.mozilla.firefox-2381.scope\n",
+        )]);
+        assert_eq!(
+            sandbox(dir.path(), PID),
+            Some(Sandbox::Flatpak("org.mozilla.firefox".to_string()))
+        );
+    }
+
+    #[test]
+    fn snap_scope_in_cgroup() {
+        let dir = proc_dir(&[(
+            "cgroup",
+            b"0::/user.slice/user-1000.slice/user@1000.service/app.slice/snap.firefox.firefox-6f8f.scope\n",
+        )]);
+        assert_eq!(
+            sandbox(dir.path(), PID),
+            Some(Sandbox::Snap("firefox".to_string()))
+        );
+    }
+
+    #[test]
+    fn flatpak_info_without_cgroup() {
+        let dir = proc_dir(&[(
+            "root/.flatpak-info",
+            b"[Application]\nname=com.spotify.Client\nruntime=runtime/org.freedesktop.Platform\n",
+        )]);
+        assert_eq!(
+            sandbox(dir.path(), PID),
+            Some(Sandbox::Flatpak("com.spotify.Client".to_string()))
+        );
+    }
+
+    #[test]
+    fn snap_executable_without_cgroup() {
+        let dir = proc_dir(&[]);
+        let exe = dir.path().join(PID.to_string()).join("exe");
+        symlink("/snap/spotify/81/usr/share/spotify/spotify", &exe).unwrap();
+        assert_eq!(
+            sandbox(dir.path(), PID),
+            Some(Sandbox::Snap("spotify".to_string()))
+        );
+    }
+
+    #[test]
+    fn plain_process_has_no_sandbox() {
+        let dir = proc_dir(&[(
+            "cgroup",
+            b"0::/user.slice/user-1000.slice/session-2.scope\n",
+        )]);
+        symlink(
+            "/usr/bin/foot",
+            dir.path().join(PID.to_string()).join("exe"),
+        )
+        .unwrap();
+        assert_eq!(sandbox(dir.path(), PID), None);
+        assert_eq!(
+            executable(dir.path(), PID).unwrap(),
+            PathBuf::from("/usr/bin/foot")
+        );
+    }
+}
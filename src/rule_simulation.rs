@@ -0,0 +1,225 @@
+use hyprland::data::{Client, FullscreenMode};
+
+use crate::rules_file::{self, ConfigRule};
+
+/// A rule that matched the window, `overridden_by` is a later rule that sets the same thing
+#[derive(Debug, Clone)]
+pub struct AppliedRule<'a> {
+    pub rule: &'a ConfigRule,
+    pub overridden_by: Option<&'a ConfigRule>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowSimulation<'a> {
+    pub client: &'a Client,
+    /// In the order Hyprland applies them
+    pub applied: Vec<AppliedRule<'a>>,
+    /// Rules that match as far as we can tell but also have a matcher we can't check, with that matcher
+    pub undecided: Vec<(&'a ConfigRule, String)>,
+}
+
+/// Effects that set the same thing, the later rule wins
+fn effect_group(effect: &str) -> &str {
+    let name = effect.split_whitespace().next().unwrap_or_default();
+    return match name {
+        "float" | "tile" => "float",
+        "move" | "center" => "move",
+        other => other,
+    };
+}
+
+fn flag_matches(pattern: &str, value: bool) -> bool {
+    return matches!(pattern.trim(), "1" | "true" | "yes") == value;
+}
+
+/// `None` when `field` isn't something the snapshot knows about
+fn matcher_matches(field: &str, pattern: &str, client: &Client) -> Option<bool> {
+    let text_matches = |value: &str| match pattern.strip_prefix("negative:") {
+        Some(pattern) => !rules_file::full_match(pattern, value),
+        None => rules_file::full_match(pattern, value),
+    };
+    return match field {
+        "class" => Some(text_matches(&client.class)),
+        "title" => Some(text_matches(&client.title)),
+        "initialClass" | "initialclass" => Some(text_matches(&client.initial_class)),
+        "initialTitle" | "initialtitle" => Some(text_matches(&client.initial_title)),
+        "floating" => Some(flag_matches(pattern, client.floating)),
+        "xwayland" => Some(flag_matches(pattern, client.xwayland)),
+        "pinned" => Some(flag_matches(pattern, client.pinned)),
+        "fullscreen" => Some(flag_matches(
+            pattern,
+            client.fullscreen != FullscreenMode::None,
+        )),
+        "workspace" => Some(match pattern.trim().strip_prefix("name:") {
+            Some(name) => name == client.workspace.name,
+            None => pattern.trim() == client.workspace.id.to_string(),
+        }),
+        _ => None,
+    };
+}
+
+enum RuleOutcome {
+    Matched,
+    NotMatched,
+    Undecided(String),
+}
+
+fn evaluate(rule: &ConfigRule, client: &Client) -> RuleOutcome {
+    let mut unknown = None;
+    for (field, pattern) in rule.line.matchers() {
+        match matcher_matches(field, pattern, client) {
+            Some(false) => return RuleOutcome::NotMatched,
+            Some(true) => (),
+            None => unknown = Some(field.to_string()),
+        }
+    }
+    return match unknown {
+        Some(field) => RuleOutcome::Undecided(field),
+        None => RuleOutcome::Matched,
+    };
+}
+
+/// Works out which window rules apply to each of `clients` like Hyprland does when the window opens:
+/// every matching rule is applied from top to bottom so when two of them set the same thing the lower one wins.
+/// The clients' current state stands in for their state when they opened.
+pub fn simulate<'a>(rules: &'a [ConfigRule], clients: &'a [Client]) -> Vec<WindowSimulation<'a>> {
+    let window_rules: Vec<&ConfigRule> = rules
+        .iter()
+        .filter(|rule| {
+            matches!(rule.line.keyword.as_str(), "windowrule" | "windowrulev2")
+                && !rule.line.matchers().is_empty()
+        })
+        .collect();
+
+    return clients
+        .iter()
+        .map(|client| {
+            let mut applied: Vec<AppliedRule> = vec![];
+            let mut undecided = vec![];
+            for rule in &window_rules {
+                match evaluate(rule, client) {
+                    RuleOutcome::Matched => {
+                        let group = effect_group(rule.line.effect());
+                        for earlier in applied.iter_mut().filter(|earlier| {
+                            earlier.overridden_by.is_none()
+                                && effect_group(earlier.rule.line.effect()) == group
+                        }) {
+                            earlier.overridden_by = Some(rule);
+                        }
+                        applied.push(AppliedRule {
+                            rule,
+                            overridden_by: None,
+                        });
+                    }
+                    RuleOutcome::Undecided(field) => undecided.push((*rule, field)),
+                    RuleOutcome::NotMatched => (),
+                }
+            }
+            return WindowSimulation {
+                client,
+                applied,
+                undecided,
+            };
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use hyprland::event_listener::WindowOpenEvent;
+    use hyprland::shared::Address;
+
+    use crate::window_events;
+
+    fn client(class: &str, title: &str) -> Client {
+        return window_events::client_from_event(&WindowOpenEvent {
+            window_address: Address::new("5f3a10"),
+            workspace_name: "1".to_string(),
+            window_class: class.to_string(),
+            window_title: title.to_string(),
+        });
+    }
+
+    fn rules(contents: &str) -> Vec<ConfigRule> {
+        return rules_file::parse_rule_lines(contents)
+            .into_iter()
+            .map(|line| ConfigRule {
+                file: PathBuf::from("window-rules.conf"),
+                line,
+            })
+            .collect();
+    }
+
+    /// `(line number, line number of the rule overriding it)` for each applied rule
+    fn applied(simulation: &WindowSimulation) -> Vec<(usize, Option<usize>)> {
+        return simulation
+            .applied
+            .iter()
+            .map(|applied| {
+                (
+                    applied.rule.line.line_number,
+                    applied.overridden_by.map(|rule| rule.line.line_number),
+                )
+            })
+            .collect();
+    }
+
+    #[test]
+    fn later_rules_override_earlier_ones_in_the_same_group() {
+        let rules = rules(
+            "windowrulev2 = float, class:foot\n\
+             windowrulev2 = size 50% 50%, class:foot\n\
+             windowrulev2 = tile, class:foot\n\
+             windowrulev2 = center, class:kitty\n\
+             windowrulev2 = move 10 10, class:foot\n\
+             windowrulev2 = center, class:foot\n",
+        );
+        let clients = [client("foot", "~")];
+
+        let simulations = simulate(&rules, &clients);
+
+        assert_eq!(
+            applied(&simulations[0]),
+            [(1, Some(3)), (2, None), (3, None), (5, Some(6)), (6, None)]
+        );
+    }
+
+    #[test]
+    fn negative_matchers_match_everything_else() {
+        let rules = rules(
+            "windowrulev2 = float, class:negative:kitty\n\
+             windowrulev2 = pin, class:negative:fo+t\n\
+             windowrulev2 = opaque, title:negative:vim.*, class:foot\n",
+        );
+        let clients = [client("foot", "vim notes.md"), client("foot", "~")];
+
+        let simulations = simulate(&rules, &clients);
+
+        assert_eq!(applied(&simulations[0]), [(1, None)]);
+        assert_eq!(applied(&simulations[1]), [(1, None), (3, None)]);
+    }
+
+    #[test]
+    fn rules_with_matchers_we_cant_check_are_undecided() {
+        let rules = rules(
+            "windowrulev2 = float, class:foot, onworkspace:w[t1]\n\
+             windowrulev2 = pin, class:kitty, onworkspace:w[t1]\n\
+             windowrulev2 = tile, floating:0, class:foot\n",
+        );
+        let clients = [client("foot", "~")];
+
+        let simulations = simulate(&rules, &clients);
+
+        assert_eq!(applied(&simulations[0]), [(3, None)]);
+        let undecided: Vec<(usize, &str)> = simulations[0]
+            .undecided
+            .iter()
+            .map(|(rule, field)| (rule.line.line_number, field.as_str()))
+            .collect();
+        // the kitty rule can't match whatever the workspace is
+        assert_eq!(undecided, [(1, "onworkspace")]);
+    }
+}
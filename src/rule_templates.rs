@@ -0,0 +1,104 @@
+use hyprland::data::Client;
+
+use crate::hyprland_config::{Parameter, RuleKind, WindowMode};
+use crate::rules_file::{self, RuleLine};
+
+/// Rules that were generated together for some other window, offered as a starting point for windows of the same class
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTemplate {
+    /// The comment above the rules, usually `class: title` of the window they were made for
+    pub source: String,
+    pub parameters: Vec<Parameter>,
+    pub modes: Vec<WindowMode>,
+}
+
+impl RuleTemplate {
+    /// The template's matchers with the titles swapped for `client`'s, `None` when there are no titles to swap
+    pub fn parameters_for(&self, client: &Client) -> Option<Vec<Parameter>> {
+        let mut swapped = false;
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|parameter| match parameter {
+                Parameter::Title(_) => {
+                    swapped = true;
+                    Parameter::title_of(client)
+                }
+                Parameter::InitialTitle(_) => {
+                    swapped = true;
+                    Parameter::initial_title_of(client)
+                }
+                other => other.clone(),
+            })
+            .collect();
+        return swapped.then_some(parameters);
+    }
+
+    fn is_for_class_of(&self, client: &Client) -> bool {
+        let header_class = self
+            .source
+            .split_once(": ")
+            .map(|(class, _)| class)
+            .unwrap_or(&self.source);
+        return header_class == client.class
+            || self.parameters.iter().any(|parameter| match parameter {
+                Parameter::Class(class) => rules_file::full_match(class, &client.class),
+                Parameter::InitialClass(class) => {
+                    rules_file::full_match(class, &client.initial_class)
+                }
+                _ => false,
+            });
+    }
+}
+
+/// Groups the window rules in `contents` by the comment above them and their matchers
+fn group_rules(contents: &str) -> Vec<RuleTemplate> {
+    let mut templates: Vec<RuleTemplate> = vec![];
+    let mut header = String::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        if let Some(comment) = line.trim().strip_prefix('#') {
+            header = comment.trim().to_string();
+            continue;
+        }
+        let Some(rule) = RuleLine::parse(index + 1, line).and_then(|rule| rule.window_rule())
+        else {
+            continue;
+        };
+        if rule.kind != RuleKind::Window {
+            continue;
+        }
+
+        match templates.last_mut() {
+            Some(template)
+                if template.source == header && template.parameters == rule.parameters =>
+            {
+                template.modes.push(rule.mode)
+            }
+            _ => templates.push(RuleTemplate {
+                source: header.clone(),
+                parameters: rule.parameters,
+                modes: vec![rule.mode],
+            }),
+        }
+    }
+
+    return templates;
+}
+
+/// Rule sets from the rules file for other windows with the same class as `client`, the ones
+/// that already apply to `client` and ones with the exact same rules as an earlier set are left out
+pub fn find_templates(contents: &str, client: &Client) -> Vec<RuleTemplate> {
+    let mut templates: Vec<RuleTemplate> = vec![];
+    for template in group_rules(contents) {
+        let is_own_rules = template.parameters_for(client).as_ref() == Some(&template.parameters);
+        if !template.is_for_class_of(client)
+            || is_own_rules
+            || templates.iter().any(|found| found.modes == template.modes)
+        {
+            continue;
+        }
+        templates.push(template);
+    }
+    return templates;
+}
@@ -0,0 +1,556 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre;
+use directories::UserDirs;
+use hyprland::data::Client;
+use regex::Regex;
+
+use crate::config::RuleSyntax;
+use crate::hyprland_config::{self, ConfigWrite, Parameter, RuleKind, WindowMode, WindowRule};
+use crate::line_diff;
+use crate::system_info;
+
+const RULE_KEYWORDS: [&str; 4] = ["windowrule", "windowrulev2", "layerrule", "workspace"];
+/// What window rules can match on, a `,` only starts the next matcher when one of these follows it
+const MATCHER_FIELDS: [&str; 21] = [
+    "class",
+    "title",
+    "initialClass",
+    "initialclass",
+    "initialTitle",
+    "initialtitle",
+    "tag",
+    "xwayland",
+    "floating",
+    "fullscreen",
+    "pinned",
+    "focus",
+    "group",
+    "modal",
+    "fullscreenstate",
+    "fullscreenState",
+    "workspace",
+    "onworkspace",
+    "content",
+    "xdgTag",
+    "xdgtag",
+];
+
+/// A rule line from the managed rules file, comments and anything that isn't a rule are left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleLine {
+    /// Starts at 1 like the line numbers in `hyprctl configerrors`
+    pub line_number: usize,
+    pub text: String,
+    pub keyword: String,
+    /// Everything on the right hand side of the `=`
+    pub value: String,
+}
+
+/// The window properties rules can match on, `None` when we don't know them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowMatch {
+    pub class: Option<String>,
+    pub title: Option<String>,
+    pub initial_class: Option<String>,
+    pub initial_title: Option<String>,
+}
+
+impl From<&Client> for WindowMatch {
+    fn from(client: &Client) -> Self {
+        return Self {
+            class: Some(client.class.clone()),
+            title: Some(client.title.clone()),
+            initial_class: Some(client.initial_class.clone()),
+            initial_title: Some(client.initial_title.clone()),
+        };
+    }
+}
+
+impl WindowMatch {
+    fn field(&self, name: &str) -> Option<&Option<String>> {
+        return match name {
+            "class" => Some(&self.class),
+            "title" => Some(&self.title),
+            "initialClass" | "initialclass" => Some(&self.initial_class),
+            "initialTitle" | "initialtitle" => Some(&self.initial_title),
+            _ => None,
+        };
+    }
+}
+
+/// Hyprland matches the whole value against the regex, not just a part of it
+pub fn full_match(pattern: &str, value: &str) -> bool {
+    return Regex::new(&format!("^(?:{})$", pattern)).is_ok_and(|regex| regex.is_match(value));
+}
+
+impl RuleLine {
+    pub fn parse(line_number: usize, text: &str) -> Option<Self> {
+        let (keyword, value) = text.split_once('=')?;
+        let keyword = keyword.trim();
+        if !RULE_KEYWORDS.contains(&keyword) {
+            return None;
+        }
+        return Some(Self {
+            line_number,
+            text: text.to_string(),
+            keyword: keyword.to_string(),
+            value: value.trim().to_string(),
+        });
+    }
+
+    /// Parses the line back into a rule, `None` for rules with effects or matchers this tool doesn't generate
+    pub fn window_rule(&self) -> Option<WindowRule> {
+        let (kind, syntax) = match self.keyword.as_str() {
+            "windowrule" => (RuleKind::Window, RuleSyntax::WindowRule),
+            "windowrulev2" => (RuleKind::Window, RuleSyntax::WindowRuleV2),
+            "layerrule" => (RuleKind::Layer, RuleSyntax::default()),
+            _ => return None,
+        };
+        let (effect, rest) = self.effect_and_rest();
+        let mode = effect.parse::<WindowMode>().ok()?;
+        let parameters = match kind {
+            RuleKind::Layer => vec![Parameter::Namespace(rest.to_string())],
+            RuleKind::Window => split_matchers(rest)
+                .into_iter()
+                .map(|part| part.parse::<Parameter>().ok())
+                .collect::<Option<Vec<Parameter>>>()?,
+        };
+        return Some(WindowRule {
+            kind,
+            syntax,
+            mode,
+            parameters,
+        });
+    }
+
+    /// The effect and everything after it, effects never have a `,` in them
+    fn effect_and_rest(&self) -> (&str, &str) {
+        let (effect, rest) = self.value.split_once(',').unwrap_or((&self.value, ""));
+        return (effect.trim(), rest.trim());
+    }
+
+    /// What the rule does, the part in front of the matchers like `float` or `size 50% 50%`
+    pub fn effect(&self) -> &str {
+        return self.effect_and_rest().0;
+    }
+
+    /// The `field:regex` parts of a window rule, the effect in front of them is skipped
+    pub fn matchers(&self) -> Vec<(&str, &str)> {
+        return split_matchers(self.effect_and_rest().1)
+            .into_iter()
+            .filter_map(|part| part.split_once(':'))
+            .collect();
+    }
+
+    /// True when every matcher we know about matches `window` and there is at least one of them,
+    /// rules that also depend on state like `floating:1` are matched on their class and title only
+    pub fn matches_window(&self, window: &WindowMatch) -> bool {
+        if self.keyword != "windowrule" && self.keyword != "windowrulev2" {
+            return false;
+        }
+        let known: Vec<(&Option<String>, &str)> = self
+            .matchers()
+            .into_iter()
+            .filter_map(|(name, pattern)| Some((window.field(name)?, pattern)))
+            .collect();
+        return !known.is_empty()
+            && known.into_iter().all(|(value, pattern)| {
+                value
+                    .as_ref()
+                    .is_some_and(|value| full_match(pattern, value))
+            });
+    }
+
+    /// True when one of the rule's class matchers matches `window`, the rest of it may still not
+    pub fn matches_class(&self, window: &WindowMatch) -> bool {
+        if self.keyword != "windowrule" && self.keyword != "windowrulev2" {
+            return false;
+        }
+        return self.matchers().into_iter().any(|(name, pattern)| {
+            matches!(name, "class" | "initialClass" | "initialclass")
+                && window
+                    .field(name)
+                    .and_then(|value| value.as_ref())
+                    .is_some_and(|value| full_match(pattern, value))
+        });
+    }
+
+    pub fn matches_layer(&self, namespace: &str) -> bool {
+        if self.keyword != "layerrule" {
+            return false;
+        }
+        let (_, pattern) = self.effect_and_rest();
+        return !pattern.is_empty() && full_match(pattern, namespace);
+    }
+}
+
+/// Splits matchers like Hyprland does, only at a `,` that's followed by a field name so one in a
+/// regex like `title:^(a, b)$` stays part of it
+fn split_matchers(text: &str) -> Vec<&str> {
+    let mut matchers = vec![];
+    let mut start = 0;
+    for (index, _) in text.match_indices(',') {
+        let next = text[index + 1..].trim_start();
+        if next
+            .split_once(':')
+            .is_some_and(|(field, _)| MATCHER_FIELDS.contains(&field))
+        {
+            matchers.push(text[start..index].trim());
+            start = index + 1;
+        }
+    }
+    matchers.push(text[start..].trim());
+    matchers.retain(|matcher| !matcher.is_empty());
+    return matchers;
+}
+
+pub fn parse_rule_lines(contents: &str) -> Vec<RuleLine> {
+    return contents
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| RuleLine::parse(index + 1, line))
+        .collect();
+}
+
+pub fn read_rule_lines() -> eyre::Result<Vec<RuleLine>> {
+    let rules_file = hyprland_config::get_rules_file()?;
+    if !rules_file.exists() {
+        return Ok(vec![]);
+    }
+    return Ok(parse_rule_lines(&fs::read_to_string(rules_file)?));
+}
+
+/// A rule line and the file it's in
+#[derive(Debug, Clone)]
+pub struct ConfigRule {
+    pub file: PathBuf,
+    pub line: RuleLine,
+}
+
+/// Window rules in the order Hyprland reads them, starting at hyprland.conf with every file it
+/// sources read in place of its `source` line. Files that don't exist are skipped.
+pub fn read_config_rules() -> eyre::Result<Vec<ConfigRule>> {
+    let mut rules = vec![];
+    read_sourced_rules(
+        &system_info::get_hyprland_config()?,
+        &mut vec![],
+        &mut rules,
+    );
+    return Ok(rules);
+}
+
+/// `reading` is the chain of files that sourced this one, so a file sourcing itself doesn't loop
+fn read_sourced_rules(file: &Path, reading: &mut Vec<PathBuf>, rules: &mut Vec<ConfigRule>) {
+    if reading.iter().any(|path| path == file) {
+        return;
+    }
+    let Ok(contents) = fs::read_to_string(file) else {
+        return;
+    };
+    reading.push(file.to_path_buf());
+    for (index, text) in contents.lines().enumerate() {
+        if let Some(pattern) = source_pattern(text) {
+            for sourced in source_paths(pattern, file.parent().unwrap_or(Path::new("/"))) {
+                read_sourced_rules(&sourced, reading, rules);
+            }
+        } else if let Some(line) = RuleLine::parse(index + 1, text) {
+            rules.push(ConfigRule {
+                file: file.to_path_buf(),
+                line,
+            });
+        }
+    }
+    reading.pop();
+}
+
+/// The path of a `source = path` line, without a trailing comment
+fn source_pattern(text: &str) -> Option<&str> {
+    let (keyword, value) = text.split_once('=')?;
+    if keyword.trim() != "source" {
+        return None;
+    }
+    let value = value.split('#').next().unwrap_or_default().trim();
+    return (!value.is_empty()).then_some(value);
+}
+
+/// The files a `source` line reads, `~` is the home directory, relative paths start at `dir` (the
+/// directory of the file with the line) and globs are expanded in sorted order like glob(3) does
+fn source_paths(pattern: &str, dir: &Path) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(relative) => match UserDirs::new() {
+            Some(user_dirs) => user_dirs.home_dir().join(relative),
+            None => return vec![],
+        },
+        None => dir.join(pattern),
+    };
+
+    let mut paths = vec![PathBuf::new()];
+    for component in path.components() {
+        let component = component.as_os_str().to_string_lossy();
+        if !component.contains(['*', '?', '[']) {
+            paths
+                .iter_mut()
+                .for_each(|path| path.push(component.as_ref()));
+            continue;
+        }
+        let Ok(regex) = Regex::new(&glob_regex(&component)) else {
+            return vec![];
+        };
+        let mut matched = vec![];
+        for path in &paths {
+            let Ok(entries) = fs::read_dir(path) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                // hidden files only match a pattern that starts with a dot
+                .filter(|name| {
+                    regex.is_match(name) && (!name.starts_with('.') || component.starts_with('.'))
+                })
+                .collect();
+            names.sort();
+            matched.extend(names.into_iter().map(|name| path.join(name)));
+        }
+        paths = matched;
+    }
+    return paths;
+}
+
+/// A regex that matches the same file names as the glob `pattern`
+fn glob_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let mut in_class = false;
+    for character in pattern.chars() {
+        match character {
+            '*' if !in_class => regex.push_str(".*"),
+            '?' if !in_class => regex.push('.'),
+            '[' if !in_class => {
+                in_class = true;
+                regex.push('[');
+            }
+            '!' if in_class && regex.ends_with('[') => regex.push('^'),
+            ']' if in_class => {
+                in_class = false;
+                regex.push(']');
+            }
+            character if in_class => regex.push(character),
+            character => regex.push_str(&regex::escape(&character.to_string())),
+        }
+    }
+    regex.push('$');
+    return regex;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineEdit {
+    Remove(RuleLine),
+    /// The new text for the line
+    Replace(RuleLine, String),
+}
+
+impl LineEdit {
+    fn rule(&self) -> &RuleLine {
+        return match self {
+            Self::Remove(rule) | Self::Replace(rule, _) => rule,
+        };
+    }
+}
+
+/// Applies `edits` to `contents`, every other line (comments, blank lines, line endings) is kept as is.
+/// Returns the new contents and the line numbers the replaced lines end up on.
+pub fn apply_line_edits(
+    contents: &str,
+    edits: &[LineEdit],
+) -> eyre::Result<(String, Vec<(usize, String)>)> {
+    let mut edited = String::with_capacity(contents.len());
+    let mut replaced = vec![];
+    let mut new_line_number = 0;
+
+    for (index, line) in contents.split_inclusive('\n').enumerate() {
+        let edit = edits
+            .iter()
+            .find(|edit| edit.rule().line_number == index + 1);
+        let text = line.trim_end_matches(['\n', '\r']);
+        if let Some(edit) = edit
+            && edit.rule().text != text
+        {
+            return Err(eyre::eyre!(
+                "Line {} changed since it was read, expected `{}` but found `{}`",
+                index + 1,
+                edit.rule().text,
+                text
+            ));
+        }
+
+        match edit {
+            Some(LineEdit::Remove(_)) => continue,
+            Some(LineEdit::Replace(_, new_text)) => {
+                new_line_number += 1;
+                edited.push_str(new_text);
+                edited.push_str(&line[text.len()..]);
+                replaced.push((new_line_number, new_text.clone()));
+            }
+            None => {
+                new_line_number += 1;
+                edited.push_str(line);
+            }
+        }
+    }
+
+    return Ok((edited, replaced));
+}
+
+/// Edits the managed rules file in place after backing it up, the result can go through `reload_and_verify`
+pub fn edit_rules_file(edits: &[LineEdit]) -> eyre::Result<ConfigWrite> {
+    let rules_file = hyprland_config::get_rules_file()?;
+    let backup = rules_file.with_extension("conf.bak");
+    let existing = fs::read_to_string(&rules_file)?;
+
+    let (edited, replaced) = apply_line_edits(&existing, edits)?;
+    fs::write(&backup, &existing)?;
+    fs::write(&rules_file, edited)?;
+
+    let (added_line_numbers, added) = replaced.into_iter().unzip();
+    return Ok(ConfigWrite {
+        path: rules_file,
+        backup,
+        added,
+        added_line_numbers,
+        removed: edits.iter().map(|edit| edit.rule().text.clone()).collect(),
+        duplicates: vec![],
+    });
+}
+
+/// Replaces the contents of `path` after backing up `current`, used to put back older versions of the file
+pub fn rewrite_rules_file(path: &Path, current: &str, new: &str) -> eyre::Result<ConfigWrite> {
+    let backup = path.with_extension("conf.bak");
+    fs::write(&backup, current)?;
+    fs::write(path, new)?;
+
+    let hunks = line_diff::diff_lines(current, new);
+    let mut added_line_numbers = vec![];
+    let mut added = vec![];
+    for hunk in &hunks {
+        for (offset, line) in hunk.added.iter().enumerate() {
+            added_line_numbers.push(hunk.new_line + offset);
+            added.push(line.clone());
+        }
+    }
+    return Ok(ConfigWrite {
+        path: path.to_path_buf(),
+        backup,
+        added,
+        added_line_numbers,
+        removed: hunks.into_iter().flat_map(|hunk| hunk.removed).collect(),
+        duplicates: vec![],
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sourced_files_are_read_in_place_of_the_source_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("hyprland.conf");
+        fs::create_dir(dir.path().join("rules")).unwrap();
+        fs::write(
+            &config,
+            "windowrule = float, class:foot\n\
+             source = rules/*.conf # the generated ones\n\
+             # source = rules/commented.txt\n\
+             source = hyprland.conf\n\
+             windowrule = tile, class:foot\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("rules/b.conf"),
+            "windowrule = pin, class:foot\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("rules/a.conf"),
+            "\nwindowrule = size 50% 50%, class:foot\nsource = ../missing.conf\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("rules/.hidden.conf"),
+            "windowrule = opaque, class:foot\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("rules/commented.txt"),
+            "windowrule = blur, class:foot\n",
+        )
+        .unwrap();
+
+        let mut rules = vec![];
+        read_sourced_rules(&config, &mut vec![], &mut rules);
+
+        let read: Vec<(PathBuf, usize, &str)> = rules
+            .iter()
+            .map(|rule| {
+                let file = rule.file.strip_prefix(dir.path()).unwrap().to_path_buf();
+                return (file, rule.line.line_number, rule.line.effect());
+            })
+            .collect();
+        assert_eq!(
+            read,
+            [
+                (PathBuf::from("hyprland.conf"), 1, "float"),
+                (PathBuf::from("rules/a.conf"), 2, "size 50% 50%"),
+                (PathBuf::from("rules/b.conf"), 1, "pin"),
+                (PathBuf::from("hyprland.conf"), 5, "tile"),
+            ]
+        );
+    }
+
+    #[test]
+    fn glob_patterns_become_anchored_regexes() {
+        assert_eq!(glob_regex("*.conf"), r"^.*\.conf$");
+        assert_eq!(glob_regex("rule?[!x].conf"), r"^rule.[^x]\.conf$");
+    }
+
+    #[test]
+    fn commas_in_a_regex_stay_in_the_matcher() {
+        let line = RuleLine::parse(
+            3,
+            r"windowrulev2 = size 50% 50%, title:^(Save, or not)$, class:a{2,3}, floating:1",
+        )
+        .unwrap();
+
+        assert_eq!(line.effect(), "size 50% 50%");
+        assert_eq!(
+            line.matchers(),
+            [
+                ("title", "^(Save, or not)$"),
+                ("class", "a{2,3}"),
+                ("floating", "1"),
+            ]
+        );
+        let window = WindowMatch {
+            class: Some("aaa".to_string()),
+            title: Some("Save, or not".to_string()),
+            ..Default::default()
+        };
+        assert!(line.matches_window(&window));
+    }
+
+    #[test]
+    fn rules_with_commas_in_a_regex_parse_back_whole() {
+        let line = RuleLine::parse(1, "windowrulev2 = float, class:foot, title:^(a, b)$").unwrap();
+
+        let rule = line.window_rule().unwrap();
+
+        assert_eq!(
+            rule.parameters,
+            [
+                Parameter::Class("foot".to_string()),
+                Parameter::Title("^(a, b)$".to_string()),
+            ]
+        );
+    }
+}
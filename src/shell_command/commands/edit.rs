@@ -0,0 +1,9 @@
+use color_eyre::Result;
+
+use crate::shell_command::commands::remove;
+use crate::shell_command::types::RuleSearchArgs;
+use crate::tui::rule_list::RuleAction;
+
+pub fn exec(args: &RuleSearchArgs) -> Result<()> {
+    remove::exec_with(args, RuleAction::Edit)
+}
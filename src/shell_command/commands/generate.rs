@@ -1,22 +1,680 @@
-use color_eyre::Result;
-
-use crate::tui::root::tui_root;
-
-pub fn exec(float: bool, _persistentsize: bool, tile: bool, _fullscreen: bool) -> Result<()> {
-    let selected_client = tui_root()?;
-    match selected_client {
-        Some(client) => {
-            println!("Selected: {}", client.title);
-            println!("Config: ");
-            if float {
-                println!("windowrule = float, initialTitle:{}", client.initial_title);
+use std::fmt::Display;
+use std::fs;
+use std::io::{IsTerminal, stdin};
+use std::path::Path;
+
+use color_eyre::{Result, eyre};
+use hyprland::ctl::reload;
+use hyprland::data::{Client, Monitor};
+use hyprland::instance::Instance;
+use hyprland::keyword::Keyword;
+
+use crate::app_info;
+use crate::config::{self, Config, RuleSyntax};
+use crate::desktop_entry;
+use crate::hyprland_config::{
+    self, ConfigWrite, ExecRule, Parameter, RuleKind, WindowMode, WindowRule, WorkspaceTarget,
+};
+use crate::hyprland_dispatch;
+use crate::hyprland_layers::LayerSurface;
+use crate::journal::Target;
+use crate::matcher_suggestion;
+use crate::notification;
+use crate::process_info;
+use crate::rule_templates::{self, RuleTemplate};
+use crate::shell_command::prompt;
+use crate::shell_command::report::{copy_rules, notify_rules, print_rules, write_rules};
+use crate::shell_command::types::GenerateArgs;
+use crate::snapshot;
+use crate::title_normalizer;
+use crate::tui::root::{SelectedWindow, Selection, tui_root, tui_root_menu};
+use crate::tui::terminal;
+use crate::window_events::{self, WindowEvent};
+use crate::window_geometry::{self, GeometryForm};
+
+/// Modes from the flags followed by the ones from `--preset`, a mode is only added once
+fn selected_modes(args: &GenerateArgs, config: &Config) -> Result<Vec<WindowMode>> {
+    let mut modes: Vec<WindowMode> = [
+        (args.float, WindowMode::Float),
+        (args.tile, WindowMode::Tile),
+        (args.fullscreen, WindowMode::Fullscreen),
+        (args.pin, WindowMode::Pin),
+        (args.persistentsize, WindowMode::PersistentSize),
+        (args.noanim, WindowMode::NoAnim),
+        (args.blur, WindowMode::Blur),
+        (args.ignorezero, WindowMode::IgnoreZero),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, mode)| if enabled { Some(mode) } else { None })
+    .collect();
+
+    for preset in &args.preset {
+        for mode in config.preset(preset)? {
+            if !modes.contains(&mode) {
+                modes.push(mode);
             }
-            if tile {
-                println!("windowrule = tile, initialTitle:{}", client.initial_title);
+        }
+    }
+    Ok(modes)
+}
+
+/// Floating windows keep their size and position so when `--geometry` isn't set the user gets asked if they want to keep them
+fn geometry_form(args: &GenerateArgs, client: &Client) -> Result<Option<GeometryForm>> {
+    if args.geometry.is_some()
+        || args.menu
+        || !client.floating
+        || !stdin().is_terminal()
+        || terminal::is_headless()
+    {
+        return Ok(args.geometry);
+    }
+    let choice = prompt::choose(
+        "Remember this window's size and position?",
+        &["pixels", "percent", "center", "no"],
+    )?;
+    return Ok(match choice {
+        "pixels" => Some(GeometryForm::Pixels),
+        "percent" => Some(GeometryForm::Percent),
+        "center" => Some(GeometryForm::Center),
+        _ => None,
+    });
+}
+
+fn print_app(client: &Client) {
+    let entries = desktop_entry::read_desktop_entries(&desktop_entry::data_dirs());
+    let app = app_info::resolve_app(Path::new(process_info::PROC_DIR), &entries, client);
+    if let Some(summary) = app.summary() {
+        println!("App: {}", summary);
+    }
+}
+
+fn print_matcher(parameters: &[Parameter]) {
+    let matcher: Vec<String> = parameters
+        .iter()
+        .map(|parameter| parameter.to_string())
+        .collect();
+    println!("Matcher: {}", matcher.join(", "));
+}
+
+fn print_also_matches(also_matches: &[Client]) {
+    if also_matches.is_empty() {
+        println!("No other open window matches it");
+    } else {
+        println!("It also matches these open windows:");
+        for other in also_matches {
+            println!("  {}: {}", other.class, other.title);
+        }
+    }
+}
+
+/// Picks a matcher for `client` and shows what else it would match
+fn suggested_parameters(client: &Client) -> Result<Vec<Parameter>> {
+    let clients = snapshot::get_clients()?;
+    let suggestion = matcher_suggestion::suggest_matcher(client, &clients);
+
+    print_matcher(&suggestion.parameters);
+    let fragments = title_normalizer::dynamic_fragments(&client.title);
+    if !fragments.is_empty() {
+        println!("Generalized in the title: {}", fragments.join(", "));
+    }
+    print_also_matches(&suggestion.also_matches);
+
+    Ok(suggestion.parameters)
+}
+
+/// Offers the rules of other windows with the same class as a starting point
+fn choose_template(client: &Client) -> Result<Option<RuleTemplate>> {
+    let rules_file = hyprland_config::get_rules_file()?;
+    if !stdin().is_terminal() || terminal::is_headless() || !rules_file.exists() {
+        return Ok(None);
+    }
+    let mut templates = rule_templates::find_templates(&fs::read_to_string(rules_file)?, client);
+    if templates.is_empty() {
+        return Ok(None);
+    }
+
+    println!("Other {} windows have rules:", client.class);
+    for (index, template) in templates.iter().enumerate() {
+        let modes: Vec<String> = template.modes.iter().map(|mode| mode.to_string()).collect();
+        println!("  {}) {}: {}", index + 1, template.source, modes.join(", "));
+    }
+    let numbers: Vec<String> = (1..=templates.len())
+        .map(|number| number.to_string())
+        .collect();
+    let mut choices: Vec<&str> = numbers.iter().map(|number| number.as_str()).collect();
+    choices.push("no");
+
+    let choice = prompt::choose("Use one as a template?", &choices)?;
+    return Ok(choice
+        .parse::<usize>()
+        .ok()
+        .map(|number| templates.swap_remove(number - 1)));
+}
+
+/// Builds a rule for each mode that makes sense for `kind`, the others are reported and dropped
+fn rules_for(
+    kind: RuleKind,
+    syntax: RuleSyntax,
+    parameters: Vec<Parameter>,
+    modes: Vec<WindowMode>,
+) -> Vec<WindowRule> {
+    return modes
+        .into_iter()
+        .filter(|mode| {
+            let applies = mode.applies_to(&kind);
+            if !applies {
+                println!("{} can't be used in a {:?} rule, skipped", mode, kind);
             }
+            return applies;
+        })
+        .map(|mode| WindowRule {
+            kind: kind.clone(),
+            syntax,
+            mode,
+            parameters: parameters.clone(),
+        })
+        .collect();
+}
+
+/// Adds the rules to the running Hyprland instance only, nothing is written until the user decides to keep them
+fn try_rules(
+    app_name: &str,
+    rules: &[WindowRule],
+    target: &Target,
+    keep: impl FnOnce() -> Result<ConfigWrite>,
+) -> Result<()> {
+    for rule in rules {
+        Keyword::set(rule.keyword(), rule.value())?;
+    }
+    println!(
+        "Rules are active until the next reload, relaunch or re-open {} to try them out.",
+        app_name
+    );
+
+    match prompt::choose("Keep these rules?", &["keep", "discard"])? {
+        "keep" => {
+            write_rules(keep()?, target)?;
+        }
+        _ => {
+            reload::call()?;
+            println!("Discarded, config reloaded");
+        }
+    };
+
+    Ok(())
+}
+
+/// `None` when the command `client` was started with can't be found, the reason gets printed.
+/// Sandboxed apps are started through their sandbox, their own command line only works inside it.
+fn exec_rule_for(client: &Client, modes: Vec<WindowMode>) -> Option<ExecRule> {
+    let proc_dir = Path::new(process_info::PROC_DIR);
+    if let Some(sandbox) = process_info::sandbox(proc_dir, client.pid) {
+        return Some(ExecRule {
+            modes,
+            command: sandbox.run_command(),
+        });
+    }
+    return match process_info::command_line(proc_dir, client.pid) {
+        Ok(args) => Some(ExecRule {
+            modes,
+            command: process_info::shell_command(&args),
+        }),
+        Err(error) => {
+            println!(
+                "Couldn't find how {} was started, skipped: {}",
+                client.class, error
+            );
+            None
+        }
+    };
+}
+
+/// Adds `rules` to the rules file under a comment saying what they're for
+fn append_rules(target: &Target, rules: &[impl Display]) -> Result<ConfigWrite> {
+    return hyprland_config::append_to_rules_file(
+        &target.label,
+        rules.iter().map(|rule| rule.to_string()).collect(),
+    );
+}
+
+/// Runs the dispatchers for `rules` on `client` for `--apply-now`
+fn apply_rules(client: &Client, monitor: Option<&Monitor>, rules: &[WindowRule]) -> Result<()> {
+    println!("Applying to {}: ", client.address);
+    for rule in rules {
+        let outcome = hyprland_dispatch::apply_rule(client, monitor, rule)?;
+        println!("{}: {}", rule.mode, outcome);
+    }
+    Ok(())
+}
+
+/// Asks in a notification when headless, tries the rules out with `--try` or writes them when
+/// `write` is set. `label` says what the rules are for in the notification and the try prompt
+fn deliver_rules(
+    args: &GenerateArgs,
+    label: &str,
+    rules: &[WindowRule],
+    target: &Target,
+    write: bool,
+) -> Result<()> {
+    // the menu already asked what to do with the rule
+    if terminal::is_headless() && !args.menu {
+        notify_rules(label, rules, target, || append_rules(target, rules))?;
+    } else if args.try_rules {
+        try_rules(label, rules, target, || append_rules(target, rules))?;
+    } else if write {
+        write_rules(append_rules(target, rules)?, target)?;
+    }
+    Ok(())
+}
+
+fn write_exec_rules(args: &GenerateArgs, label: &str, rules: &[ExecRule]) -> Result<()> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+    print_rules(label, rules);
+    if args.copy {
+        copy_rules(rules)?;
+    }
+    let target = Target::from(format!("startup: {}", label));
+    // --try doesn't go with --exec-once, startup lines can't be tried without restarting
+    if terminal::is_headless() {
+        notify_rules(label, rules, &target, || append_rules(&target, rules))?;
+    } else if args.write {
+        write_rules(append_rules(&target, rules)?, &target)?;
+    }
+    Ok(())
+}
+
+fn exec_window(
+    args: &GenerateArgs,
+    config: &Config,
+    mut modes: Vec<WindowMode>,
+    SelectedWindow { client, monitor }: SelectedWindow,
+) -> Result<()> {
+    if let Some(form) = geometry_form(args, &client)? {
+        if !client.floating && !modes.contains(&WindowMode::Float) {
+            println!("Size and position rules only apply to floating windows, add --float");
         }
+        modes.extend(window_geometry::geometry_modes(
+            &client,
+            monitor.as_ref(),
+            form,
+        ));
+    }
+    if args.to_workspace {
+        modes.push(window_geometry::workspace_mode(&client, args.silent));
+    }
+    match (args.to_monitor, &monitor) {
+        (Some(matcher), Some(monitor)) => {
+            modes.push(window_geometry::monitor_mode(monitor, matcher))
+        }
+        (Some(_), None) => println!("Couldn't find the window's monitor, skipping monitor rule"),
         _ => (),
     };
+    print_app(&client);
+    if args.exec_once {
+        let rules: Vec<ExecRule> = exec_rule_for(&client, modes).into_iter().collect();
+        return write_exec_rules(args, &client.class, &rules);
+    }
+    let mut parameters = match config.parameters_for(&client) {
+        Some(parameters) => parameters,
+        None => suggested_parameters(&client)?,
+    };
+    let template = match args.menu {
+        true => None,
+        false => choose_template(&client)?,
+    };
+    if let Some(template) = template {
+        if let Some(swapped) = template.parameters_for(&client) {
+            parameters = swapped;
+        }
+        for mode in template.modes {
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+    }
+    let rules = rules_for(RuleKind::Window, config.syntax, parameters, modes);
+
+    print_rules(&client.title, &rules);
+    if args.copy {
+        copy_rules(&rules)?;
+    }
+
+    if args.apply_now && !client.mapped {
+        println!(
+            "{} closed before it could be looked up, skipped applying",
+            client.class
+        );
+    } else if args.apply_now {
+        apply_rules(&client, monitor.as_ref(), &rules)?;
+    }
+
+    return deliver_rules(
+        args,
+        &client.class,
+        &rules,
+        &Target::from(&client),
+        args.write || args.menu,
+    );
+}
+
+fn exec_layer(
+    args: &GenerateArgs,
+    modes: Vec<WindowMode>,
+    layer_surface: LayerSurface,
+) -> Result<()> {
+    let layer = layer_surface.layer;
+    let rules = rules_for(
+        RuleKind::Layer,
+        RuleSyntax::default(),
+        vec![Parameter::Namespace(layer.namespace.clone())],
+        modes,
+    );
+
+    print_rules(&format!("layer {}", layer.namespace), &rules);
+    if args.copy {
+        copy_rules(&rules)?;
+    }
+
+    if args.apply_now {
+        println!("Layer rules can't be applied with dispatchers, skipped");
+    }
+
+    return deliver_rules(
+        args,
+        &layer.namespace,
+        &rules,
+        &Target::from(format!("layer: {}", layer.namespace)),
+        args.write,
+    );
+}
+
+/// The workspace, float state, size and position that put `client` back where it is
+fn layout_modes(
+    args: &GenerateArgs,
+    modes: &[WindowMode],
+    client: &Client,
+    monitor: &Monitor,
+    workspace: &WorkspaceTarget,
+) -> Vec<WindowMode> {
+    let mut window_modes = modes.to_vec();
+    window_modes.push(WindowMode::Workspace(workspace.clone(), true));
+    if client.floating {
+        window_modes.push(WindowMode::Float);
+        window_modes.extend(window_geometry::geometry_modes(
+            client,
+            Some(monitor),
+            args.geometry.unwrap_or(GeometryForm::Pixels),
+        ));
+    } else {
+        window_modes.push(WindowMode::Tile);
+    }
+    if let Some(matcher) = args.to_monitor {
+        window_modes.push(window_geometry::monitor_mode(monitor, matcher));
+    }
+    let mut deduplicated: Vec<WindowMode> = vec![];
+    for mode in window_modes {
+        if !deduplicated.contains(&mode) {
+            deduplicated.push(mode);
+        }
+    }
+    return deduplicated;
+}
+
+/// One set of rules for several windows picked together, with a single matcher that covers all of them
+fn exec_windows(
+    args: &GenerateArgs,
+    config: &Config,
+    modes: Vec<WindowMode>,
+    selected: Vec<SelectedWindow>,
+) -> Result<()> {
+    let clients: Vec<Client> = selected
+        .iter()
+        .map(|window| window.client.clone())
+        .collect();
+    let classes: Vec<&str> = clients.iter().map(|client| client.class.as_str()).collect();
+    let label = classes.join(", ");
+    if args.geometry.is_some() || args.to_workspace || args.to_monitor.is_some() {
+        println!("Size, position, workspace and monitor rules are per window, skipped");
+    }
+    for client in &clients {
+        println!("Window: {}: {}", client.class, client.title);
+    }
+    if args.exec_once {
+        let rules: Vec<ExecRule> = clients
+            .iter()
+            .filter_map(|client| exec_rule_for(client, modes.clone()))
+            .collect();
+        return write_exec_rules(args, &label, &rules);
+    }
+
+    let suggestion =
+        matcher_suggestion::suggest_combined_matcher(&clients, &snapshot::get_clients()?);
+    print_matcher(&suggestion.parameters);
+    print_also_matches(&suggestion.also_matches);
+    let rules = rules_for(
+        RuleKind::Window,
+        config.syntax,
+        suggestion.parameters,
+        modes,
+    );
+    print_rules(&label, &rules);
+    if args.copy {
+        copy_rules(&rules)?;
+    }
+
+    if args.apply_now {
+        for window in selected.iter().filter(|window| window.client.mapped) {
+            apply_rules(&window.client, window.monitor.as_ref(), &rules)?;
+        }
+    }
+
+    return deliver_rules(
+        args,
+        &label,
+        &rules,
+        &Target::from(format!("group: {}", label)),
+        args.write,
+    );
+}
+
+/// Rules for every window on the focused monitor's workspace so launching the same apps lays it out
+/// the same way again. Floating windows keep their size and position, tiled ones only their workspace
+/// since the layout decides the rest. The windows open silently so restoring doesn't jump between workspaces.
+fn exec_layout(args: &GenerateArgs, config: &Config, modes: Vec<WindowMode>) -> Result<()> {
+    let monitors = snapshot::get_monitors()?;
+    let Some(monitor) = monitors.iter().find(|monitor| monitor.focused) else {
+        return Err(eyre::eyre!("No monitor is focused"));
+    };
+    let workspace = WorkspaceTarget::from(&monitor.active_workspace);
+    let clients: Vec<Client> = snapshot::get_clients()?
+        .into_iter()
+        .filter(|client| client.mapped && client.workspace.id == monitor.active_workspace.id)
+        .collect();
+    if clients.is_empty() {
+        println!("No windows on workspace {}", workspace);
+        return Ok(());
+    }
+
+    if args.exec_once {
+        let rules: Vec<ExecRule> = clients
+            .iter()
+            .filter_map(|client| {
+                exec_rule_for(
+                    client,
+                    layout_modes(args, &modes, client, monitor, &workspace),
+                )
+            })
+            .collect();
+        return write_exec_rules(args, &format!("layout: {}", workspace), &rules);
+    }
+
+    let mut layout_rules = vec![];
+    for client in &clients {
+        let window_modes = layout_modes(args, &modes, client, monitor, &workspace);
+        let parameters = match config.parameters_for(client) {
+            Some(parameters) => parameters,
+            None => suggested_parameters(client)?,
+        };
+        let rules = rules_for(RuleKind::Window, config.syntax, parameters, window_modes);
+        print_rules(&client.title, &rules);
+
+        if args.apply_now {
+            apply_rules(client, Some(monitor), &rules)?;
+        }
+        layout_rules.extend(rules);
+    }
+    if args.copy {
+        copy_rules(&layout_rules)?;
+    }
+
+    return deliver_rules(
+        args,
+        &format!("the apps on workspace {}", workspace),
+        &layout_rules,
+        &Target::from(format!("layout: {}", workspace)),
+        args.write,
+    );
+}
+
+/// Waits for the next `count` windows to open, each one is looked up as soon as it opens so
+/// windows that close right away are still captured with everything Hyprland knows about them
+fn watch_windows(count: usize) -> Result<Vec<SelectedWindow>> {
+    let events = window_events::listen(Instance::from_current_env()?);
+    println!("Waiting for {} window(s) to open...", count);
+
+    let mut captured: Vec<SelectedWindow> = vec![];
+    while captured.len() < count {
+        let Ok(event) = events.recv() else {
+            return Err(eyre::eyre!("Hyprland's event socket was closed"));
+        };
+        let WindowEvent::Opened(opened) = event? else {
+            continue;
+        };
+
+        let clients = snapshot::get_clients()?;
+        let monitors = snapshot::get_monitors()?;
+        let selected = match clients
+            .into_iter()
+            .find(|client| client.address == opened.window_address)
+        {
+            Some(client) => SelectedWindow {
+                monitor: monitors
+                    .into_iter()
+                    .find(|monitor| client.monitor == Some(monitor.id)),
+                client,
+            },
+            None => SelectedWindow {
+                client: window_events::client_from_event(&opened),
+                monitor: None,
+            },
+        };
+        println!(
+            "  {}) {}: {}",
+            captured.len() + 1,
+            selected.client.initial_class,
+            selected.client.initial_title
+        );
+        captured.push(selected);
+    }
+
+    Ok(captured)
+}
+
+/// Captures the windows first and builds the rules for them one at a time afterwards
+fn exec_watch(
+    args: &GenerateArgs,
+    config: &Config,
+    modes: Vec<WindowMode>,
+    count: usize,
+) -> Result<()> {
+    let mut captured = watch_windows(count)?;
+
+    while !captured.is_empty() {
+        let index = if captured.len() == 1 {
+            0
+        } else {
+            println!("Captured windows:");
+            for (index, selected) in captured.iter().enumerate() {
+                println!(
+                    "  {}) {}: {}",
+                    index + 1,
+                    selected.client.initial_class,
+                    selected.client.initial_title
+                );
+            }
+            let numbers: Vec<String> = (1..=captured.len())
+                .map(|number| number.to_string())
+                .collect();
+            let mut choices: Vec<&str> = numbers.iter().map(|number| number.as_str()).collect();
+            choices.push("done");
+
+            match prompt::choose("Make rules for which window?", &choices)?.parse::<usize>() {
+                Ok(number) => number - 1,
+                Err(_) => break,
+            }
+        };
+        exec_window(args, config, modes.clone(), captured.remove(index))?;
+    }
 
     Ok(())
 }
+
+fn generate(args: &GenerateArgs) -> Result<()> {
+    // before the picker so a typo in a preset doesn't waste a pick
+    let config = config::load_config()?;
+    let modes = selected_modes(args, &config)?;
+    terminal::set_headless(args.headless);
+    // the windows in a snapshot may not be open anymore, or be other windows by now
+    if args.apply_now {
+        snapshot::refuse_live("--apply-now")?;
+    }
+    if args.try_rules {
+        snapshot::refuse_live("--try")?;
+    }
+    if args.watch.is_some() {
+        snapshot::refuse_live("--watch")?;
+    }
+
+    if let Some(count) = args.watch {
+        return exec_watch(args, &config, modes, count);
+    }
+    if args.workspace {
+        return exec_layout(args, &config, modes);
+    }
+    if args.menu {
+        if let Some((selected_window, mode)) = tui_root_menu()? {
+            let mut modes = modes;
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+            exec_window(args, &config, modes, selected_window)?;
+        }
+        return Ok(());
+    }
+
+    match tui_root(args.layers)? {
+        Some(Selection::Window(selected_window)) => {
+            exec_window(args, &config, modes, *selected_window)?
+        }
+        Some(Selection::Windows(selected_windows)) => {
+            exec_windows(args, &config, modes, selected_windows)?
+        }
+        Some(Selection::Layer(selected_layer)) => exec_layer(args, modes, selected_layer)?,
+        None => (),
+    };
+
+    Ok(())
+}
+
+pub fn exec(args: &GenerateArgs) -> Result<()> {
+    let result = generate(args);
+    // nobody is watching the terminal when headless
+    if args.headless
+        && let Err(error) = &result
+    {
+        let _ = notification::notify("Couldn't generate the rules", &error.to_string());
+    }
+    return result;
+}
@@ -0,0 +1,38 @@
+use color_eyre::Result;
+
+use crate::journal::{self, JournalEntry};
+
+fn print_entry(entry: &JournalEntry, undone: bool) {
+    println!(
+        "#{} {} UTC  {}  {}  (+{} -{}){}",
+        entry.id,
+        journal::format_timestamp(entry.timestamp),
+        entry.action,
+        entry.target.label,
+        entry.added_count(),
+        entry.removed_count(),
+        if undone { "  undone" } else { "" }
+    );
+    for hunk in &entry.hunks {
+        for line in &hunk.removed {
+            println!("    - {}", line);
+        }
+        for line in &hunk.added {
+            println!("    + {}", line);
+        }
+    }
+}
+
+pub fn exec() -> Result<()> {
+    let entries = journal::read_journal()?;
+    if entries.is_empty() {
+        println!("Nothing in {} yet", journal::get_journal_file()?.display());
+        return Ok(());
+    }
+
+    let undone = journal::undone_ids(&entries);
+    for entry in &entries {
+        print_entry(entry, undone.contains(&entry.id));
+    }
+    Ok(())
+}
@@ -1,2 +1,11 @@
+pub mod edit;
 pub mod generate;
+pub mod history;
+pub mod monitor;
 pub mod options_exec;
+pub mod remove;
+pub mod serve_clipboard;
+pub mod simulate;
+pub mod snapshot;
+pub mod undo;
+pub mod workspace;
@@ -0,0 +1,137 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use color_eyre::{Result, eyre};
+use hyprland::data::Clients;
+use hyprland::instance::Instance;
+use hyprland::prelude::*;
+
+use crate::event_log::{EventLog, LoggedEvent};
+use crate::rules_file;
+use crate::shell_command::types::MonitorArgs;
+use crate::tui::event_table;
+use crate::window_events::{self, WindowEvent};
+
+/// Logs `event`, the rules are read again when Hyprland reloads its config
+fn handle(log: &mut EventLog, event: WindowEvent) -> Result<Option<LoggedEvent>> {
+    if let WindowEvent::ConfigReloaded = event {
+        log.set_rules(rules_file::read_config_rules()?);
+    }
+    Ok(log.log(event))
+}
+
+fn print_json_lines(mut log: EventLog, events: Receiver<Result<WindowEvent>>) -> Result<()> {
+    for event in events {
+        if let Some(logged) = handle(&mut log, event?)? {
+            println!("{}", serde_json::to_string(&logged)?);
+        }
+    }
+    Err(eyre::eyre!("Hyprland's event socket was closed"))
+}
+
+fn show_table(mut log: EventLog, events: Receiver<Result<WindowEvent>>) -> Result<()> {
+    return event_table::show_events(|| {
+        let mut logged = vec![];
+        loop {
+            match events.try_recv() {
+                Ok(event) => logged.extend(handle(&mut log, event?)?),
+                Err(TryRecvError::Empty) => return Ok(logged),
+                Err(TryRecvError::Disconnected) => {
+                    return Err(eyre::eyre!("Hyprland's event socket was closed"));
+                }
+            }
+        }
+    });
+}
+
+pub fn exec(args: &MonitorArgs) -> Result<()> {
+    let instance = match &args.instance {
+        Some(path) => Instance::from_base_socket_path(path.clone())?,
+        None => Instance::from_current_env()?,
+    };
+    // the open windows are looked up first so events about them can be matched against the rules
+    let clients = match Clients::instance_get(&instance) {
+        Ok(clients) => clients.to_vec(),
+        // windows that were already open are only known once they open again
+        Err(error) => {
+            eprintln!(
+                "Couldn't get the open windows, only new ones will be logged: {}",
+                error
+            );
+            vec![]
+        }
+    };
+    let log = EventLog::new(&clients, rules_file::read_config_rules()?);
+    let events = window_events::listen(instance);
+
+    if args.json {
+        return print_json_lines(log, events);
+    }
+    return show_table(log, events);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::rules_file::{ConfigRule, parse_rule_lines};
+
+    /// Next event from the socket that gets logged
+    fn next_logged(log: &mut EventLog, events: &Receiver<Result<WindowEvent>>) -> LoggedEvent {
+        loop {
+            let event = events
+                .recv_timeout(Duration::from_secs(5))
+                .expect("an event from the socket")
+                .unwrap();
+            if let Some(logged) = log.log(event) {
+                return logged;
+            }
+        }
+    }
+
+    #[test]
+    fn events_from_the_socket_are_matched_against_the_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = UnixListener::bind(dir.path().join(".socket2.sock")).unwrap();
+        let events = window_events::listen(
+            Instance::from_base_socket_path(dir.path().to_path_buf()).unwrap(),
+        );
+        let (mut stream, _) = socket.accept().unwrap();
+        stream
+            .write_all(b"openwindow>>5f3a10,1,foot,~\nwindowtitlev2>>5f3a10,vim notes.md\n")
+            .unwrap();
+
+        let rules: Vec<ConfigRule> = parse_rule_lines(
+            "windowrule = float, class:foot, title:vim.*\nwindowrule = pin, class:kitty\n",
+        )
+        .into_iter()
+        .map(|line| ConfigRule {
+            file: PathBuf::from("window-rules.conf"),
+            line,
+        })
+        .collect();
+        let mut log = EventLog::new(&[], rules);
+
+        let opened = next_logged(&mut log, &events);
+        assert_eq!(opened.event, "openwindow");
+        assert_eq!(
+            (opened.class.as_str(), opened.title.as_str()),
+            ("foot", "~")
+        );
+        assert_eq!(opened.detail, "workspace 1");
+        // the class matches but the title doesn't yet
+        assert_eq!(opened.rules.len(), 1);
+        assert_eq!(opened.rules[0].line_number, 1);
+        assert!(!opened.rules[0].fired);
+
+        let retitled = next_logged(&mut log, &events);
+        assert_eq!(retitled.event, "windowtitle");
+        assert_eq!(retitled.title, "vim notes.md");
+        assert_eq!(retitled.address, opened.address);
+        assert_eq!(retitled.rules.len(), 1);
+        assert!(retitled.rules[0].fired);
+    }
+}
@@ -12,11 +12,12 @@ struct Args {
 pub fn version() -> String {
     let author = clap::crate_authors!();
 
-    let commit_hash = env!("RATATUI_TEMPLATE_GIT_INFO");
+    let commit_hash = env!("HYPRLAND_WINDOW_RULE_GIT_INFO");
 
     // let current_exe_path = PathBuf::from(clap::crate_name!()).display().to_string();
     let config_dir_path = get_config_dir().unwrap().display().to_string();
     let data_dir_path = get_data_dir().unwrap().display().to_string();
+    let hyprland_config_path = get_hyprland_config().unwrap().display().to_string();
 
     format!(
         "\
@@ -25,6 +26,7 @@ pub fn version() -> String {
 Authors: {author}
 
 Config directory: {config_dir_path}
-Data directory: {data_dir_path}"
+Data directory: {data_dir_path}
+Hyprland config: {hyprland_config_path}"
     )
 }
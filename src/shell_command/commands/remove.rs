@@ -0,0 +1,137 @@
+use color_eyre::Result;
+
+use crate::hyprland_config;
+use crate::journal::{self, JournalAction, Target};
+use crate::rules_file::{self, LineEdit, RuleLine, WindowMatch};
+use crate::shell_command::types::RuleSearchArgs;
+use crate::tui::root::{Selection, tui_root};
+use crate::tui::rule_list::{RuleAction, pick_rule_edits};
+
+/// Rules in the managed rules file for the window given on the command line or picked in the overlay,
+/// along with what they were matched against
+pub fn find_rules(args: &RuleSearchArgs) -> Result<Option<(Target, Vec<RuleLine>)>> {
+    let rules = rules_file::read_rule_lines()?;
+
+    if args.class.is_some() || args.title.is_some() {
+        let window = WindowMatch {
+            class: args.class.clone(),
+            title: args.title.clone(),
+            initial_class: args.class.clone(),
+            initial_title: args.title.clone(),
+        };
+        let label = [args.class.as_deref(), args.title.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&str>>()
+            .join(": ");
+        let matching = rules
+            .into_iter()
+            .filter(|rule| rule.matches_window(&window))
+            .collect();
+        return Ok(Some((Target::from(label), matching)));
+    }
+
+    return Ok(match tui_root(args.layers)? {
+        Some(Selection::Window(selected_window)) => {
+            let client = &selected_window.client;
+            let window = WindowMatch::from(client);
+            Some((
+                Target::from(client),
+                rules
+                    .into_iter()
+                    .filter(|rule| rule.matches_window(&window))
+                    .collect(),
+            ))
+        }
+        Some(Selection::Windows(selected_windows)) => {
+            let windows: Vec<WindowMatch> = selected_windows
+                .iter()
+                .map(|selected| WindowMatch::from(&selected.client))
+                .collect();
+            let classes: Vec<&str> = selected_windows
+                .iter()
+                .map(|selected| selected.client.class.as_str())
+                .collect();
+            Some((
+                Target::from(classes.join(", ")),
+                rules
+                    .into_iter()
+                    .filter(|rule| windows.iter().any(|window| rule.matches_window(window)))
+                    .collect(),
+            ))
+        }
+        Some(Selection::Layer(layer_surface)) => {
+            let namespace = layer_surface.layer.namespace;
+            Some((
+                Target::from(format!("layer: {}", namespace)),
+                rules
+                    .into_iter()
+                    .filter(|rule| rule.matches_layer(&namespace))
+                    .collect(),
+            ))
+        }
+        None => None,
+    });
+}
+
+/// Writes the edits picked in the rule list and rolls them back if Hyprland doesn't accept the result
+pub fn apply_edits(edits: Vec<LineEdit>, target: &Target) -> Result<()> {
+    if edits.is_empty() {
+        println!("Nothing changed");
+        return Ok(());
+    }
+
+    let written = rules_file::edit_rules_file(&edits)?;
+    let errors = journal::record_if_failed(
+        hyprland_config::reload_and_verify(&written),
+        JournalAction::Edit,
+        target,
+        &written,
+    )?;
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}", error);
+        }
+        println!(
+            "Hyprland rejected the edited rules, {} has been restored",
+            written.path.display()
+        );
+        return Ok(());
+    }
+    journal::record(JournalAction::Edit, target, &written)?;
+
+    for edit in &edits {
+        match edit {
+            LineEdit::Remove(rule) => println!("Removed: {}", rule.text),
+            LineEdit::Replace(rule, text) => println!("Edited: {}\n    -> {}", rule.text, text),
+        };
+    }
+    println!(
+        "Updated {}, the previous version is in {}",
+        written.path.display(),
+        written.backup.display()
+    );
+    Ok(())
+}
+
+/// Shared by `remove` and `edit`, they only differ in what enter does in the rule list
+pub fn exec_with(args: &RuleSearchArgs, action: RuleAction) -> Result<()> {
+    let Some((target, rules)) = find_rules(args)? else {
+        return Ok(());
+    };
+    if rules.is_empty() {
+        println!(
+            "No rules for {} in {}",
+            target.label,
+            hyprland_config::get_rules_file()?.display()
+        );
+        return Ok(());
+    }
+
+    let edits = pick_rule_edits(&target.label, rules, action)?;
+    apply_edits(edits, &target)
+}
+
+pub fn exec(args: &RuleSearchArgs) -> Result<()> {
+    exec_with(args, RuleAction::Remove)
+}
@@ -0,0 +1,19 @@
+use std::io::{Read, Write, stdin, stdout};
+
+use color_eyre::Result;
+
+use crate::wayland::clipboard;
+
+/// Reports back to `clipboard::copy` on stdout, which is gone once it has read the first line
+fn report(line: &dyn std::fmt::Display) {
+    let _ = writeln!(stdout(), "{}", line);
+}
+
+pub fn exec() -> Result<()> {
+    let mut text = String::new();
+    stdin().read_to_string(&mut text)?;
+    if let Err(error) = clipboard::serve(text, || report(&clipboard::READY)) {
+        report(&error);
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::io::{Read, stdin};
+use std::path::Path;
+
+use color_eyre::{Result, eyre};
+use hyprland::data::Client;
+
+use crate::rule_simulation::{self, WindowSimulation};
+use crate::rules_file::{self, ConfigRule};
+use crate::shell_command::types::SimulateArgs;
+use crate::snapshot;
+
+/// Clients from a `hyprctl clients -j` dump, `-` reads it from stdin
+fn read_clients(path: &Path) -> Result<Vec<Client>> {
+    let contents = if path == Path::new("-") {
+        let mut contents = String::new();
+        stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(path)?
+    };
+    return serde_json::from_str(&contents)
+        .map_err(|error| eyre::eyre!("Invalid clients in {}: {}", path.display(), error));
+}
+
+fn location(rule: &ConfigRule) -> String {
+    let file = rule
+        .file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    return format!("{}:{}", file, rule.line.line_number);
+}
+
+fn print_simulation(simulation: &WindowSimulation) {
+    println!("{}: {}", simulation.client.class, simulation.client.title);
+    if simulation.applied.is_empty() && simulation.undecided.is_empty() {
+        println!("  no rules");
+    }
+    for applied in &simulation.applied {
+        match applied.overridden_by {
+            None => println!(
+                "  {:<30} {}",
+                applied.rule.line.effect(),
+                location(applied.rule)
+            ),
+            Some(later) => println!(
+                "  {:<30} {}, overridden by {}",
+                applied.rule.line.effect(),
+                location(applied.rule),
+                location(later)
+            ),
+        }
+    }
+    for (rule, field) in &simulation.undecided {
+        println!(
+            "  {:<30} {}, maybe, can't check {}",
+            rule.line.effect(),
+            location(rule),
+            field
+        );
+    }
+}
+
+pub fn exec(args: &SimulateArgs) -> Result<()> {
+    let clients = match &args.clients {
+        Some(path) => read_clients(path)?,
+        None => snapshot::get_clients()?,
+    };
+    let rules = rules_file::read_config_rules()?;
+
+    for simulation in rule_simulation::simulate(&rules, &clients) {
+        print_simulation(&simulation);
+    }
+    Ok(())
+}
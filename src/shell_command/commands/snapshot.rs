@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use color_eyre::{Result, eyre};
+
+use crate::journal;
+use crate::shell_command::types::{SnapshotArgs, SnapshotCommand};
+use crate::snapshot::{self, Snapshot};
+
+fn describe(name: &str, snapshot: &Snapshot) -> String {
+    return format!(
+        "{}  {} UTC  {} windows, {} monitors, {} layer surfaces",
+        name,
+        journal::format_timestamp(snapshot.timestamp),
+        snapshot.clients.len(),
+        snapshot.monitors.len(),
+        snapshot
+            .layers
+            .values()
+            .flat_map(|display| display.levels.values())
+            .map(|layers| layers.len())
+            .sum::<usize>()
+    );
+}
+
+fn save(name: Option<&str>) -> Result<()> {
+    let snapshot = snapshot::capture()?;
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => journal::format_timestamp(snapshot.timestamp)
+            .replace(' ', "_")
+            .replace(':', "-"),
+    };
+    let path = snapshot::write_snapshot(&name, &snapshot)?;
+    println!("Saved {} to {}", describe(&name, &snapshot), path.display());
+    Ok(())
+}
+
+fn load(file: &Path, name: Option<&str>) -> Result<()> {
+    let snapshot = snapshot::read_snapshot(file)?;
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .ok_or_else(|| eyre::eyre!("{} has no file name, pass --name", file.display()))?,
+    };
+    let path = snapshot::write_snapshot(&name, &snapshot)?;
+    println!(
+        "Imported {} to {}",
+        describe(&name, &snapshot),
+        path.display()
+    );
+    println!("Use it with --snapshot {}", name);
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let snapshots = snapshot::list_snapshots()?;
+    if snapshots.is_empty() {
+        println!(
+            "No snapshots in {} yet",
+            snapshot::get_snapshot_dir()?.display()
+        );
+    }
+    for (name, snapshot) in snapshots {
+        println!("{}", describe(&name, &snapshot));
+    }
+    Ok(())
+}
+
+pub fn exec(args: &SnapshotArgs) -> Result<()> {
+    return match &args.command {
+        SnapshotCommand::Save { name } => save(name.as_deref()),
+        SnapshotCommand::Load { file, name } => load(file, name.as_deref()),
+        SnapshotCommand::List => list(),
+    };
+}
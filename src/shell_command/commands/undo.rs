@@ -0,0 +1,70 @@
+use std::fs;
+
+use color_eyre::Result;
+
+use crate::hyprland_config;
+use crate::journal::{self, JournalAction, JournalEntry};
+use crate::line_diff;
+use crate::rules_file;
+use crate::shell_command::types::UndoArgs;
+
+/// Takes the lines `entry` changed back out of the file, returns false when that didn't work and undoing should stop
+fn undo_entry(entry: &JournalEntry) -> Result<bool> {
+    let current = if entry.path.exists() {
+        fs::read_to_string(&entry.path)?
+    } else {
+        String::new()
+    };
+    let reverted = match line_diff::revert_hunks(&current, &entry.hunks) {
+        Ok(reverted) => reverted,
+        Err(error) => {
+            println!("Can't undo #{}: {}", entry.id, error);
+            return Ok(false);
+        }
+    };
+
+    let written = rules_file::rewrite_rules_file(&entry.path, &current, &reverted)?;
+    let errors = journal::record_if_failed(
+        hyprland_config::reload_and_verify(&written),
+        JournalAction::Undo(entry.id),
+        &entry.target,
+        &written,
+    )?;
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}", error);
+        }
+        println!(
+            "Hyprland rejected the file without #{}, {} has been restored",
+            entry.id,
+            written.path.display()
+        );
+        return Ok(false);
+    }
+    journal::record(JournalAction::Undo(entry.id), &entry.target, &written)?;
+
+    println!(
+        "Undid #{} {} for {} (+{} -{})",
+        entry.id,
+        entry.action,
+        entry.target.label,
+        entry.added_count(),
+        entry.removed_count()
+    );
+    Ok(true)
+}
+
+pub fn exec(args: &UndoArgs) -> Result<()> {
+    let entries = journal::undoable(&journal::read_journal()?, args.count);
+    if entries.is_empty() {
+        println!("Nothing to undo");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        if !undo_entry(entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
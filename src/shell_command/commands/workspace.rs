@@ -0,0 +1,112 @@
+use color_eyre::{Result, eyre};
+use hyprland::data::{Monitor, WorkspaceBasic};
+
+use crate::hyprland_config::{self, WorkspaceOption, WorkspaceRule, WorkspaceTarget};
+use crate::journal::Target;
+use crate::shell_command::report::{copy_rules, print_rules, write_rules};
+use crate::shell_command::types::WorkspaceArgs;
+use crate::snapshot;
+use crate::tui::root::pick_monitor;
+use crate::window_geometry;
+
+fn selected_options(args: &WorkspaceArgs, monitor: Option<&Monitor>) -> Vec<WorkspaceOption> {
+    let mut options = vec![];
+    match (args.monitor, monitor) {
+        (Some(matcher), Some(monitor)) => options.push(WorkspaceOption::Monitor(
+            window_geometry::monitor_selector(monitor, matcher),
+        )),
+        (Some(_), None) => println!("Couldn't find the workspace's monitor, skipping monitor"),
+        _ => (),
+    };
+    if args.default && monitor.is_some() {
+        options.push(WorkspaceOption::Default);
+    }
+    if let Some(gaps) = args.gaps_in {
+        options.push(WorkspaceOption::GapsIn(gaps));
+    }
+    if let Some(gaps) = args.gaps_out {
+        options.push(WorkspaceOption::GapsOut(gaps));
+    }
+    if let Some(size) = args.border_size {
+        options.push(WorkspaceOption::BorderSize(size));
+    }
+    if args.no_border {
+        options.push(WorkspaceOption::Border(false));
+    }
+    if args.no_rounding {
+        options.push(WorkspaceOption::Rounding(false));
+    }
+    if args.persistent {
+        options.push(WorkspaceOption::Persistent);
+    }
+    if let Some(command) = &args.on_created_empty {
+        options.push(WorkspaceOption::OnCreatedEmpty(command.clone()));
+    }
+    return options;
+}
+
+/// Looks the workspace up by id or name, workspaces that don't exist yet can still get rules
+fn find_workspace(workspace: &str) -> Result<(WorkspaceTarget, Option<Monitor>)> {
+    let monitors = snapshot::get_monitors()?;
+    let target = WorkspaceTarget::from(workspace);
+    let open_workspace = snapshot::get_workspaces()?.into_iter().find(|open| {
+        let basic = WorkspaceBasic {
+            id: open.id,
+            name: open.name.clone(),
+        };
+        WorkspaceTarget::from(&basic) == target || open.name == workspace
+    });
+
+    let Some(open_workspace) = open_workspace else {
+        println!(
+            "Workspace {} isn't open, the rule is generated anyway",
+            target
+        );
+        return Ok((target, None));
+    };
+    let monitor = monitors
+        .into_iter()
+        .find(|monitor| monitor.name == open_workspace.monitor);
+    return Ok((
+        WorkspaceTarget::from(&WorkspaceBasic {
+            id: open_workspace.id,
+            name: open_workspace.name,
+        }),
+        monitor,
+    ));
+}
+
+pub fn exec(args: &WorkspaceArgs) -> Result<()> {
+    let (workspace, monitor) = match &args.workspace {
+        Some(workspace) => find_workspace(workspace)?,
+        None => match pick_monitor()? {
+            Some(monitor) => (
+                WorkspaceTarget::from(&monitor.active_workspace),
+                Some(monitor),
+            ),
+            None => return Ok(()),
+        },
+    };
+
+    let options = selected_options(args, monitor.as_ref());
+    if options.is_empty() {
+        return Err(eyre::eyre!(
+            "Nothing to put in the rule, pass at least one option like --monitor or --persistent"
+        ));
+    }
+    let rule = WorkspaceRule { workspace, options };
+
+    print_rules(&format!("workspace {}", rule.workspace), &[&rule]);
+    if args.copy {
+        copy_rules(&[&rule])?;
+    }
+
+    if args.write {
+        write_rules(
+            hyprland_config::generate_workspace_config_for(&rule)?,
+            &Target::from(format!("workspace: {}", rule.workspace)),
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,2 +1,4 @@
 pub mod commands;
+pub mod prompt;
+pub mod report;
 pub mod types;
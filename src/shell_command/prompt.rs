@@ -0,0 +1,24 @@
+use std::io::{Write, stdin, stdout};
+
+use color_eyre::eyre;
+
+/// Prints `question` and blocks until one of `choices` is entered, the first character of a choice also counts
+pub fn choose<'c>(question: &str, choices: &[&'c str]) -> eyre::Result<&'c str> {
+    loop {
+        print!("{} [{}]: ", question, choices.join("/"));
+        stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin().read_line(&mut answer)? == 0 {
+            return Err(eyre::eyre!("stdin closed before an answer was given"));
+        }
+        let answer = answer.trim().to_lowercase();
+
+        let choice = choices.iter().find(|choice| {
+            answer == **choice || (answer.len() == 1 && choice.starts_with(answer.as_str()))
+        });
+        if let Some(choice) = choice {
+            return Ok(choice);
+        }
+    }
+}
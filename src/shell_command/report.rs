@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use color_eyre::Result;
+
+use crate::config;
+use crate::hyprland_config::{self, ConfigWrite};
+use crate::journal::{self, JournalAction, Target};
+use crate::notification::{self, NotificationAction};
+use crate::wayland::clipboard;
+
+pub fn print_rules(selected: &str, rules: &[impl Display]) {
+    println!("Selected: {}", selected);
+    println!("Config: ");
+    for rule in rules {
+        println!("{}", rule);
+    }
+}
+
+/// Puts the rules on the clipboard the way they'd be written to the config
+pub fn copy_rules(rules: &[impl Display]) -> Result<()> {
+    let text: String = rules.iter().map(|rule| format!("{}\n", rule)).collect();
+    clipboard::copy(&text)?;
+    println!("Copied {} rule(s) to the clipboard", rules.len());
+    Ok(())
+}
+
+/// Reports on rules written to the managed rules file and rolls it back if Hyprland doesn't accept
+/// them, returns whether it did
+pub fn write_rules(written: ConfigWrite, target: &Target) -> Result<bool> {
+    for duplicate in &written.duplicates {
+        println!("Already in config, skipped: {}", duplicate);
+    }
+
+    let errors = journal::record_if_failed(
+        hyprland_config::reload_and_verify(&written),
+        JournalAction::Write,
+        target,
+        &written,
+    )?;
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}", error);
+        }
+        println!(
+            "Hyprland rejected the new rules, {} has been restored",
+            written.path.display()
+        );
+        return Ok(false);
+    }
+    journal::record(JournalAction::Write, target, &written)?;
+
+    println!(
+        "Wrote {} rule(s) to {}",
+        written.added.len(),
+        written.path.display()
+    );
+    if !config::load_config()?.auto_reload {
+        println!("auto_reload is off in the config, run `hyprctl reload` to load them");
+    }
+    if !hyprland_config::is_rules_file_sourced()? {
+        println!(
+            "Add `source = {}` to your hyprland.conf to load it",
+            written.path.display()
+        );
+    }
+    Ok(true)
+}
+
+/// Headless mode has nowhere to print so the rules are shown in a notification, its buttons do
+/// what `--write` and `--copy` would
+pub fn notify_rules(
+    selected: &str,
+    rules: &[impl Display],
+    target: &Target,
+    write: impl FnOnce() -> Result<ConfigWrite>,
+) -> Result<()> {
+    let text: Vec<String> = rules.iter().map(|rule| rule.to_string()).collect();
+    let action = notification::ask(
+        &format!("Rules for {}", selected),
+        &text.join("\n"),
+        &[
+            NotificationAction {
+                key: "write",
+                label: "Write to config",
+            },
+            NotificationAction {
+                key: "copy",
+                label: "Copy",
+            },
+            NotificationAction {
+                key: "discard",
+                label: "Discard",
+            },
+        ],
+    )?;
+
+    match action {
+        Some("write") => {
+            let written = write()?;
+            let path = written.path.display().to_string();
+            if write_rules(written, target)? {
+                notification::notify("Rules written", &path)?;
+            } else {
+                notification::notify(
+                    "Hyprland rejected the rules",
+                    &format!("{} has been restored", path),
+                )?;
+            }
+        }
+        Some("copy") => {
+            if let Err(error) = copy_rules(rules) {
+                notification::notify("Couldn't copy the rules", &error.to_string())?;
+            }
+        }
+        _ => (),
+    };
+    Ok(())
+}
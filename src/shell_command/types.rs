@@ -1,4 +1,8 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::window_geometry::{GeometryForm, MonitorMatcher};
 
 #[derive(Parser)]
 #[command(about, long_about = None)]
@@ -14,23 +18,290 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub debug: u8,
 
+    /// Hyprland config file to use instead of the one Hyprland would find, same as Hyprland's `--config`
+    #[arg(short, long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Read the windows and monitors from a saved snapshot instead of the running Hyprland, a name or a path
+    #[arg(long, global = true, value_name = "NAME")]
+    pub snapshot: Option<String>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    Generate {
-        #[arg(long, help = "add float rule")]
-        float: bool,
+    Generate(GenerateArgs),
+    Workspace(WorkspaceArgs),
+    Remove(RuleSearchArgs),
+    Edit(RuleSearchArgs),
+    History,
+    Undo(UndoArgs),
+    Monitor(MonitorArgs),
+    Simulate(SimulateArgs),
+    Snapshot(SnapshotArgs),
+    /// Serves text from stdin as the clipboard selection, started by `--copy`
+    #[command(name = "serve-clipboard", hide = true)]
+    ServeClipboard,
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    #[arg(long, help = "add float rule")]
+    pub float: bool,
+
+    #[arg(long, help = "add persistentsize rule")]
+    pub persistentsize: bool,
+
+    #[arg(long, help = "add tile rule")]
+    pub tile: bool,
+
+    #[arg(long, help = "add fullscreen rule")]
+    pub fullscreen: bool,
+
+    #[arg(long, help = "add pin rule")]
+    pub pin: bool,
+
+    #[arg(long, help = "add noanim rule")]
+    pub noanim: bool,
+
+    #[arg(long, help = "add blur rule, layers only")]
+    pub blur: bool,
+
+    #[arg(long, help = "add ignorezero rule, layers only")]
+    pub ignorezero: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "add the rules from a preset in the config file, can be given more than once"
+    )]
+    pub preset: Vec<String>,
+
+    #[arg(
+        long,
+        help = "also let layer surfaces (bars, launchers, notifications) be picked"
+    )]
+    pub layers: bool,
+
+    #[arg(
+        long,
+        value_name = "COUNT",
+        num_args = 0..=1,
+        default_missing_value = "1",
+        conflicts_with_all = ["layers", "workspace"],
+        help = "wait for the next COUNT windows to open instead of picking one, for windows that close too fast to click"
+    )]
+    pub watch: Option<usize>,
+
+    #[arg(
+        long,
+        conflicts_with = "layers",
+        help = "generate rules for every window on the active workspace that put them back where they are now"
+    )]
+    pub workspace: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["layers", "try_rules"],
+        help = "generate exec-once lines that start the apps with the rules instead of window rules"
+    )]
+    pub exec_once: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "add size and position rules from where the window currently is"
+    )]
+    pub geometry: Option<GeometryForm>,
+
+    #[arg(
+        long,
+        help = "add a rule to open the window on the workspace it's on now"
+    )]
+    pub to_workspace: bool,
+
+    #[arg(
+        long,
+        requires = "to_workspace",
+        help = "open the window on its workspace without switching to it"
+    )]
+    pub silent: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "add a rule to open the window on the monitor it's on now"
+    )]
+    pub to_monitor: Option<MonitorMatcher>,
+
+    #[arg(
+        long,
+        help = "apply the generated rules to the selected window right away using hyprctl dispatch"
+    )]
+    pub apply_now: bool,
+
+    #[arg(
+        long = "try",
+        help = "inject the rules with hyprctl keyword until you decide to keep or discard them"
+    )]
+    pub try_rules: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "try_rules",
+        help = "write the rules to the managed rules file and reload Hyprland"
+    )]
+    pub write: bool,
+
+    #[arg(long, help = "copy the rules to the clipboard")]
+    pub copy: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["watch", "try_rules"],
+        help = "only show the overlay and ask what to do with the rules in a desktop notification, for keybinds"
+    )]
+    pub headless: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["layers", "watch", "workspace", "try_rules", "exec_once"],
+        help = "pick the rule from a menu drawn next to the clicked window, the rule is written right away"
+    )]
+    pub menu: bool,
+}
 
-        #[arg(long, help = "add persistentsize rule")]
-        persistentsize: bool,
+#[derive(Args)]
+pub struct WorkspaceArgs {
+    #[arg(help = "workspace id or name, click a monitor to use its active workspace when not set")]
+    pub workspace: Option<String>,
 
-        #[arg(long, help = "add tile rule")]
-        tile: bool,
+    #[arg(
+        long,
+        value_enum,
+        help = "bind the workspace to the monitor it's on now"
+    )]
+    pub monitor: Option<MonitorMatcher>,
+
+    #[arg(
+        long,
+        requires = "monitor",
+        help = "make it the workspace the monitor opens on"
+    )]
+    pub default: bool,
+
+    #[arg(long, help = "gaps between windows")]
+    pub gaps_in: Option<u32>,
+
+    #[arg(long, help = "gaps between windows and the monitor edges")]
+    pub gaps_out: Option<u32>,
+
+    #[arg(long, help = "border size of windows on the workspace")]
+    pub border_size: Option<u32>,
+
+    #[arg(long, help = "turn off window borders")]
+    pub no_border: bool,
+
+    #[arg(long, help = "turn off window rounding")]
+    pub no_rounding: bool,
+
+    #[arg(long, help = "keep the workspace around when it's empty")]
+    pub persistent: bool,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "command to run when the workspace is created empty"
+    )]
+    pub on_created_empty: Option<String>,
+
+    #[arg(
+        long,
+        help = "write the rule to the managed rules file and reload Hyprland"
+    )]
+    pub write: bool,
+
+    #[arg(long, help = "copy the rule to the clipboard")]
+    pub copy: bool,
+}
+
+#[derive(Args)]
+pub struct RuleSearchArgs {
+    #[arg(
+        long,
+        help = "find rules matching this class instead of picking a window"
+    )]
+    pub class: Option<String>,
+
+    #[arg(
+        long,
+        help = "find rules matching this title instead of picking a window"
+    )]
+    pub title: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["class", "title"],
+        help = "also let layer surfaces be picked"
+    )]
+    pub layers: bool,
+}
+
+#[derive(Args)]
+pub struct UndoArgs {
+    #[arg(default_value_t = 1, help = "how many changes to undo, newest first")]
+    pub count: usize,
+}
+
+#[derive(Args)]
+pub struct MonitorArgs {
+    #[arg(
+        long,
+        help = "print the events as JSON lines instead of showing a table"
+    )]
+    pub json: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Hyprland instance directory with the event socket, like $XDG_RUNTIME_DIR/hypr/$HYPRLAND_INSTANCE_SIGNATURE"
+    )]
+    pub instance: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct SimulateArgs {
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "windows from `hyprctl clients -j` to check the rules against instead of the open ones, - for stdin"
+    )]
+    pub clients: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub command: SnapshotCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotCommand {
+    /// Save the open windows, monitors and workspaces
+    Save {
+        #[arg(help = "name to save it as, the current date and time when not set")]
+        name: Option<String>,
+    },
+    /// Import a snapshot file, like one from a bug report
+    Load {
+        #[arg(help = "snapshot file to import")]
+        file: PathBuf,
 
-        #[arg(long, help = "add fullscreen rule")]
-        fullscreen: bool,
+        #[arg(long, help = "name to import it as, the file name when not set")]
+        name: Option<String>,
     },
+    /// List the saved snapshots
+    List,
 }
@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::eyre;
+use hyprland::data::{
+    Client, Clients, LayerDisplay, Layers, Monitor, Monitors, Workspace, Workspaces,
+};
+use hyprland::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::system_info;
+
+pub const SNAPSHOT_DIR_NAME: &str = "snapshots";
+
+/// The windows, monitors, workspaces and layer surfaces at some point, in the same form `hyprctl -j` gives them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Seconds since the unix epoch
+    pub timestamp: u64,
+    pub clients: Vec<Client>,
+    pub monitors: Vec<Monitor>,
+    #[serde(default)]
+    pub workspaces: Vec<Workspace>,
+    /// Monitor name -> level -> layer surfaces, like `hyprctl layers -j`
+    #[serde(default)]
+    pub layers: HashMap<String, LayerDisplay>,
+}
+
+/// Set from `--snapshot`, everything reads from it instead of asking Hyprland
+static ACTIVE_SNAPSHOT: OnceLock<Snapshot> = OnceLock::new();
+
+pub fn get_snapshot_dir() -> eyre::Result<PathBuf> {
+    return Ok(system_info::get_data_dir()?.join(SNAPSHOT_DIR_NAME));
+}
+
+/// `name` is a snapshot in the snapshot directory, or a path to a snapshot file when it has a `/` in it
+pub fn snapshot_path(name: &str) -> eyre::Result<PathBuf> {
+    if name.contains('/') {
+        return Ok(PathBuf::from(name));
+    }
+    return Ok(get_snapshot_dir()?.join(format!("{}.json", name)));
+}
+
+/// What Hyprland has open right now
+pub fn capture() -> eyre::Result<Snapshot> {
+    return Ok(Snapshot {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        clients: Clients::get()?.to_vec(),
+        monitors: Monitors::get()?.to_vec(),
+        workspaces: Workspaces::get()?.to_vec(),
+        layers: Layers::get()?.into_iter().collect(),
+    });
+}
+
+pub fn read_snapshot(path: &Path) -> eyre::Result<Snapshot> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| eyre::eyre!("Couldn't read {}: {}", path.display(), error))?;
+    return serde_json::from_str(&contents)
+        .map_err(|error| eyre::eyre!("Invalid snapshot in {}: {}", path.display(), error));
+}
+
+/// Writes `snapshot` to the snapshot directory as `name`, returns where it went
+pub fn write_snapshot(name: &str, snapshot: &Snapshot) -> eyre::Result<PathBuf> {
+    return write_snapshot_in(&get_snapshot_dir()?, name, snapshot);
+}
+
+fn write_snapshot_in(dir: &Path, name: &str, snapshot: &Snapshot) -> eyre::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", name));
+    fs::write(&path, serde_json::to_string_pretty(snapshot)?)?;
+    return Ok(path);
+}
+
+/// Names of the snapshots in the snapshot directory, oldest first. Files that can't be read are
+/// skipped with a warning so one broken file doesn't hide the rest
+pub fn list_snapshots() -> eyre::Result<Vec<(String, Snapshot)>> {
+    let dir = get_snapshot_dir()?;
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut snapshots = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let Some(name) = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        match read_snapshot(&path) {
+            Ok(snapshot) => snapshots.push((name, snapshot)),
+            Err(error) => eprintln!("Skipping a snapshot: {}", error),
+        };
+    }
+    snapshots.sort_by_key(|(_, snapshot)| snapshot.timestamp);
+    return Ok(snapshots);
+}
+
+pub fn use_snapshot(name: &str) -> eyre::Result<()> {
+    let snapshot = read_snapshot(&snapshot_path(name)?)?;
+    let _ = ACTIVE_SNAPSHOT.set(snapshot);
+    return Ok(());
+}
+
+pub fn is_active() -> bool {
+    return ACTIVE_SNAPSHOT.get().is_some();
+}
+
+/// Errors when a snapshot is in use, for things that would act on whatever the running Hyprland
+/// has open rather than on the windows in the snapshot
+pub fn refuse_live(what: &str) -> eyre::Result<()> {
+    if is_active() {
+        return Err(eyre::eyre!(
+            "{} acts on the running Hyprland, it can't be used with --snapshot",
+            what
+        ));
+    }
+    return Ok(());
+}
+
+pub fn get_clients() -> eyre::Result<Vec<Client>> {
+    if let Some(snapshot) = ACTIVE_SNAPSHOT.get() {
+        return Ok(snapshot.clients.clone());
+    }
+    return Ok(Clients::get()?.to_vec());
+}
+
+pub fn get_monitors() -> eyre::Result<Vec<Monitor>> {
+    if let Some(snapshot) = ACTIVE_SNAPSHOT.get() {
+        return Ok(snapshot.monitors.clone());
+    }
+    return Ok(Monitors::get()?.to_vec());
+}
+
+pub fn get_workspaces() -> eyre::Result<Vec<Workspace>> {
+    if let Some(snapshot) = ACTIVE_SNAPSHOT.get() {
+        return Ok(snapshot.workspaces.clone());
+    }
+    return Ok(Workspaces::get()?.to_vec());
+}
+
+pub fn get_layers() -> eyre::Result<HashMap<String, LayerDisplay>> {
+    if let Some(snapshot) = ACTIVE_SNAPSHOT.get() {
+        return Ok(snapshot.layers.clone());
+    }
+    return Ok(Layers::get()?.into_iter().collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyprland::data::{LayerClient, Transforms, WorkspaceBasic};
+    use hyprland::event_listener::WindowOpenEvent;
+    use hyprland::shared::Address;
+
+    use crate::window_events;
+
+    fn snapshot() -> Snapshot {
+        let workspace = WorkspaceBasic {
+            id: 1,
+            name: "1".to_string(),
+        };
+        return Snapshot {
+            timestamp: 1_700_000_000,
+            clients: vec![window_events::client_from_event(&WindowOpenEvent {
+                window_address: Address::new("5f3a10"),
+                workspace_name: "1".to_string(),
+                window_class: "foot".to_string(),
+                window_title: "~".to_string(),
+            })],
+            monitors: vec![Monitor {
+                id: 0,
+                name: "DP-1".to_string(),
+                description: "Dell Inc. DELL U2720Q".to_string(),
+                width: 3840,
+                height: 2160,
+                refresh_rate: 60.0,
+                x: 0,
+                y: 0,
+                active_workspace: workspace.clone(),
+                special_workspace: WorkspaceBasic {
+                    id: 0,
+                    name: String::new(),
+                },
+                reserved: (0, 30, 0, 0),
+                scale: 1.5,
+                transform: Transforms::Normal,
+                focused: true,
+                dpms_status: true,
+                vrr: false,
+                disabled: false,
+            }],
+            workspaces: vec![],
+            layers: HashMap::from([(
+                "DP-1".to_string(),
+                LayerDisplay {
+                    levels: HashMap::from([(
+                        "2".to_string(),
+                        vec![LayerClient {
+                            address: Address::new("7b1c00"),
+                            x: 0,
+                            y: 0,
+                            w: 2560,
+                            h: 30,
+                            namespace: "waybar".to_string(),
+                        }],
+                    )]),
+                },
+            )]),
+        };
+    }
+
+    #[test]
+    fn saved_snapshots_load_the_same() {
+        let dir = tempfile::tempdir().unwrap();
+        let saved = snapshot();
+
+        let path = write_snapshot_in(dir.path(), "layout", &saved).unwrap();
+
+        assert_eq!(path, dir.path().join("layout.json"));
+        assert_eq!(read_snapshot(&path).unwrap(), saved);
+    }
+
+    #[test]
+    fn an_active_snapshot_stands_in_for_hyprland() {
+        let dir = tempfile::tempdir().unwrap();
+        let saved = snapshot();
+        let path = write_snapshot_in(dir.path(), "layout", &saved).unwrap();
+
+        // a path, a plain name would be looked up in the snapshot directory
+        use_snapshot(path.to_str().unwrap()).unwrap();
+
+        assert_eq!(get_clients().unwrap(), saved.clients);
+        assert_eq!(get_monitors().unwrap(), saved.monitors);
+        assert_eq!(get_layers().unwrap(), saved.layers);
+        assert!(refuse_live("--apply-now").is_err());
+    }
+}
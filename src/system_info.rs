@@ -1,43 +1,103 @@
-use std::path::PathBuf;
+use std::{env, fs, path::PathBuf, sync::OnceLock};
 
-use color_eyre::eyre::{self, WrapErr};
+use color_eyre::eyre::{self};
 use directories::{ProjectDirs, UserDirs};
 
+const APPLICATION: &str = "hyprland-window-rule";
+
+/// Set from `--config`, takes priority over everything else like Hyprland's own `--config` does
+static HYPRLAND_CONFIG: OnceLock<PathBuf> = OnceLock::new();
+
+fn project_dirs() -> eyre::Result<ProjectDirs> {
+    return ProjectDirs::from("", "", APPLICATION)
+        .ok_or_else(|| eyre::eyre!("Unable to find a home directory for {}", APPLICATION));
+}
+
+/// Where versions from before the rename kept their files
+fn legacy_project_dirs() -> Option<ProjectDirs> {
+    return ProjectDirs::from("com", "kdheepak", "ratatui-template");
+}
+
 pub fn get_data_dir() -> eyre::Result<PathBuf> {
-    let directory = if let Ok(s) = std::env::var("RATATUI_TEMPLATE_DATA") {
-        PathBuf::from(s)
-    } else if let Some(proj_dirs) = ProjectDirs::from("com", "kdheepak", "ratatui-template") {
-        proj_dirs.data_local_dir().to_path_buf()
-    } else {
-        return Err(eyre::eyre!(
-            "Unable to find data directory for ratatui-template"
-        ));
-    };
-    Ok(directory)
+    if let Ok(s) = env::var("HYPRLAND_WINDOW_RULE_DATA") {
+        return Ok(PathBuf::from(s));
+    }
+    return Ok(project_dirs()?.data_local_dir().to_path_buf());
 }
 
 pub fn get_config_dir() -> eyre::Result<PathBuf> {
-    let directory = if let Ok(s) = std::env::var("RATATUI_TEMPLATE_CONFIG") {
-        PathBuf::from(s)
-    } else if let Some(proj_dirs) = ProjectDirs::from("com", "kdheepak", "ratatui-template") {
-        proj_dirs.config_local_dir().to_path_buf()
-    } else {
-        return Err(eyre::eyre!(
-            "Unable to find config directory for ratatui-template"
-        ));
+    if let Ok(s) = env::var("HYPRLAND_WINDOW_RULE_CONFIG") {
+        return Ok(PathBuf::from(s));
+    }
+    return Ok(project_dirs()?.config_local_dir().to_path_buf());
+}
+
+/// Moves the data and config directories over from the template's names, directories that
+/// already exist under the new name are left alone
+pub fn migrate_legacy_dirs() -> eyre::Result<()> {
+    let Some(legacy) = legacy_project_dirs() else {
+        return Ok(());
     };
-    Ok(directory)
+    let moves = [
+        (legacy.data_local_dir().to_path_buf(), get_data_dir()?),
+        (legacy.config_local_dir().to_path_buf(), get_config_dir()?),
+    ];
+    for (old, new) in moves {
+        if !old.is_dir() || new.exists() {
+            continue;
+        }
+        if let Some(parent) = new.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&old, &new).map_err(|error| {
+            eyre::eyre!(
+                "Couldn't move {} to {}: {}",
+                old.display(),
+                new.display(),
+                error
+            )
+        })?;
+        eprintln!("Moved {} to {}", old.display(), new.display());
+    }
+    return Ok(());
 }
 
-pub fn get_hyprland_dir() -> eyre::Result<PathBuf> {
-    let directory = if let Ok(s) = std::env::var("HYPRLAND_CONFIG_DIR") {
-        PathBuf::from(s)
-    } else if let Some(home_dir) = UserDirs::new() {
-        home_dir.home_dir().join(".config/hypr/")
-    } else {
-        return Err(eyre::eyre!(
-            "Unable to find data directory for ratatui-template"
-        ));
+pub fn set_hyprland_config(path: PathBuf) {
+    let _ = HYPRLAND_CONFIG.set(path);
+}
+
+/// Finds hyprland.conf the same way Hyprland does, `--config` then `HYPRLAND_CONFIG`
+/// then `$XDG_CONFIG_HOME/hypr` then `~/.config/hypr`. `HYPRLAND_CONFIG_DIR` from before
+/// `--config` existed still works and comes right after `HYPRLAND_CONFIG`
+pub fn get_hyprland_config() -> eyre::Result<PathBuf> {
+    if let Some(path) = HYPRLAND_CONFIG.get() {
+        return Ok(path.clone());
+    }
+    if let Ok(path) = env::var("HYPRLAND_CONFIG")
+        && !path.is_empty()
+    {
+        return Ok(PathBuf::from(path));
+    }
+    if let Ok(dir) = env::var("HYPRLAND_CONFIG_DIR")
+        && !dir.is_empty()
+    {
+        return Ok(PathBuf::from(dir).join("hyprland.conf"));
+    }
+    if let Ok(config_home) = env::var("XDG_CONFIG_HOME")
+        && !config_home.is_empty()
+    {
+        return Ok(PathBuf::from(config_home).join("hypr/hyprland.conf"));
+    }
+    let Some(user_dirs) = UserDirs::new() else {
+        return Err(eyre::eyre!("Unable to find the Hyprland config directory"));
     };
-    Ok(directory)
+    return Ok(user_dirs.home_dir().join(".config/hypr/hyprland.conf"));
+}
+
+pub fn get_hyprland_dir() -> eyre::Result<PathBuf> {
+    let config = get_hyprland_config()?;
+    return config
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .ok_or_else(|| eyre::eyre!("{} has no parent directory", config.display()));
 }
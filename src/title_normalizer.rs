@@ -0,0 +1,226 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+const TITLE_SEPARATORS: [&str; 4] = [" - ", " — ", " – ", " | "];
+
+/// A part of a window title that changes while the window is open
+pub struct DynamicFragment {
+    pub name: &'static str,
+    /// Matched against the title
+    pub find: &'static str,
+    /// Regex that goes in the rule in place of what `find` matched
+    pub replace_with: &'static str,
+    /// Only look for it at the start of a word, paths would otherwise match the `/` in `TCP/IP`
+    pub at_word_start: bool,
+}
+
+/// Checked in order at each position of the title, the first one that matches there wins.
+/// Add new kinds of dynamic titles here.
+pub const DYNAMIC_FRAGMENTS: &[DynamicFragment] = &[
+    DynamicFragment {
+        name: "unread counter",
+        find: r"^\(\d+\) ",
+        replace_with: r"(\(\d+\) )?",
+        at_word_start: true,
+    },
+    DynamicFragment {
+        name: "unsaved marker",
+        find: r"^(\* |● )",
+        replace_with: r"(\* |● )?",
+        at_word_start: true,
+    },
+    DynamicFragment {
+        name: "unread count",
+        find: r"\(\d+\)|\[\d+\]",
+        replace_with: r"(\(\d+\)|\[\d+\])",
+        at_word_start: false,
+    },
+    DynamicFragment {
+        name: "date",
+        find: r"\d{4}-\d{2}-\d{2}",
+        replace_with: r"\d{4}-\d{2}-\d{2}",
+        at_word_start: true,
+    },
+    DynamicFragment {
+        name: "time",
+        find: r"\d\d?:\d{2}(:\d{2})?(\s?[AaPp][Mm])?",
+        // no `{1,2}`, the comma would end the matcher
+        replace_with: r"\d\d?:\d{2}(:\d{2})?(\s?[AaPp][Mm])?",
+        at_word_start: true,
+    },
+    DynamicFragment {
+        name: "path",
+        find: r"(~|\.\.?)?/\S*",
+        replace_with: r"\S*",
+        at_word_start: true,
+    },
+    DynamicFragment {
+        name: "file name",
+        find: r"[\w-]+(\.[\w-]+)*\.[A-Za-z][A-Za-z0-9]{0,4}\b",
+        replace_with: r"\S+",
+        at_word_start: true,
+    },
+    DynamicFragment {
+        name: "number",
+        find: r"\d+",
+        replace_with: r"\d+",
+        at_word_start: false,
+    },
+];
+
+static COMPILED_FRAGMENTS: LazyLock<Vec<(Regex, &'static DynamicFragment)>> = LazyLock::new(|| {
+    return DYNAMIC_FRAGMENTS
+        .iter()
+        .map(|fragment| {
+            let regex = Regex::new(fragment.find).expect("dynamic fragment regexes are valid");
+            (regex, fragment)
+        })
+        .collect();
+});
+
+/// Escapes `text` so it can go in a rule as a regex, `#` would start a comment and `,` would end the matcher
+pub fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            '#' => escaped.push_str(r"\x23"),
+            ',' => escaped.push_str(r"\x2c"),
+            _ => escaped.push(char),
+        }
+    }
+    return escaped;
+}
+
+/// Escapes `title` with every dynamic fragment in it swapped for a regex that matches any value of it,
+/// also returns the names of the fragments that were found and whether any letters or digits were left as they are
+fn scan(title: &str) -> (String, Vec<&'static str>, bool) {
+    let mut pattern = String::with_capacity(title.len());
+    let mut found_fragments = vec![];
+    let mut has_literal_text = false;
+    let mut index = 0;
+
+    while let Some(char) = title[index..].chars().next() {
+        let at_word_start = title[..index]
+            .chars()
+            .next_back()
+            .is_none_or(|previous| !previous.is_alphanumeric());
+        let fragment = COMPILED_FRAGMENTS
+            .iter()
+            .filter(|(_, fragment)| at_word_start || !fragment.at_word_start)
+            .find_map(|(regex, fragment)| {
+                let found = regex.find_at(title, index)?;
+                return (found.start() == index && !found.is_empty())
+                    .then_some((found.end(), *fragment));
+            });
+
+        match fragment {
+            Some((end, fragment)) => {
+                pattern.push_str(fragment.replace_with);
+                found_fragments.push(fragment.name);
+                index = end;
+            }
+            None => {
+                has_literal_text |= char.is_alphanumeric();
+                pattern.push_str(&escape_regex(&char.to_string()));
+                index += char.len_utf8();
+            }
+        }
+    }
+
+    return (pattern, found_fragments, has_literal_text);
+}
+
+pub fn generalize(title: &str) -> String {
+    return scan(title).0;
+}
+
+/// Names of the dynamic fragments `generalize` would swap out of `title`
+pub fn dynamic_fragments(title: &str) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = vec![];
+    for name in scan(title).1 {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    return names;
+}
+
+/// Title regex for rules, the dynamic parts are generalized and an app name at the end is
+/// left out when `class` already says which app it is, `Inbox - Mozilla Firefox` becomes `Inbox.*`.
+/// The app name is kept when nothing else in the title would be matched as is, `\S+.*` for
+/// `file.rs - Visual Studio Code` would match the title of nearly any window.
+pub fn normalize(title: &str, class: &str) -> String {
+    let app_suffix = TITLE_SEPARATORS.iter().find_map(|separator| {
+        let (front, app) = title.rsplit_once(separator)?;
+        let is_app_name = !class.is_empty() && app.to_lowercase().contains(&class.to_lowercase());
+        return is_app_name.then_some(front);
+    });
+
+    return match app_suffix.map(scan) {
+        Some((front, _, true)) => format!("{}.*", front),
+        _ => generalize(title),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unread_counter_at_the_start_is_optional() {
+        assert_eq!(
+            generalize("(3) Slack | general"),
+            r"(\(\d+\) )?Slack \| general"
+        );
+        assert_eq!(
+            dynamic_fragments("(3) Slack | general"),
+            vec!["unread counter"]
+        );
+    }
+
+    #[test]
+    fn app_name_is_left_out_when_the_class_names_it() {
+        assert_eq!(normalize("Inbox - Mozilla Firefox", "firefox"), r"Inbox.*");
+        assert_eq!(
+            normalize("file.rs - Visual Studio Code", "Code"),
+            r"\S+ - Visual Studio Code"
+        );
+        assert_eq!(
+            normalize("(2) 12:03 - Slack", "Slack"),
+            r"(\(\d+\) )?\d\d?:\d{2}(:\d{2})?(\s?[AaPp][Mm])? - Slack"
+        );
+        assert_eq!(
+            normalize("file.rs - Visual Studio Code", "kitty"),
+            r"\S+ - Visual Studio Code"
+        );
+    }
+
+    #[test]
+    fn time_pattern_has_no_comma() {
+        let pattern = generalize("12:03 - Clock");
+        assert!(!pattern.contains(','), "{}", pattern);
+        assert!(
+            Regex::new(&format!("^{}$", pattern))
+                .unwrap()
+                .is_match("9:41 - Clock")
+        );
+        assert_eq!(dynamic_fragments("12:03 - Clock"), vec!["time"]);
+    }
+
+    #[test]
+    fn paths_only_start_at_word_start() {
+        assert_eq!(generalize("TCP/IP Settings"), "TCP/IP Settings");
+        assert_eq!(generalize("~/src/crate - Files"), r"\S* - Files");
+    }
+
+    #[test]
+    fn version_numbers_are_not_taken_for_file_names() {
+        assert_eq!(generalize("Version 2.0 Setup"), r"Version \d+\.\d+ Setup");
+        assert_eq!(dynamic_fragments("Version 2.0 Setup"), vec!["number"]);
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use super::terminal;
+use crate::event_log::LoggedEvent;
+use crate::journal;
+
+use color_eyre::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table, TableState};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Model {
+    events: Vec<LoggedEvent>,
+    table: TableState,
+    /// Keep the newest event selected as they come in
+    follow: bool,
+    quit: bool,
+}
+
+/// Shows the events from `next_events` as they come in until the user quits, `next_events` shouldn't block
+pub fn show_events(mut next_events: impl FnMut() -> Result<Vec<LoggedEvent>>) -> Result<()> {
+    terminal::install_panic_hook();
+    let mut model = Model {
+        events: vec![],
+        table: TableState::default(),
+        follow: true,
+        quit: false,
+    };
+    let result = run(&mut model, &mut next_events);
+    terminal::restore_terminal()?;
+    return result;
+}
+
+fn run(
+    model: &mut Model,
+    next_events: &mut impl FnMut() -> Result<Vec<LoggedEvent>>,
+) -> Result<()> {
+    let mut terminal = terminal::init_terminal()?;
+    while !model.quit {
+        model.events.extend(next_events()?);
+        if model.follow && !model.events.is_empty() {
+            model.table.select(Some(model.events.len() - 1));
+        }
+        terminal.draw(|f| view(model, f))?;
+
+        if event::poll(POLL_INTERVAL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            update(model, key.code);
+        }
+    }
+    return Ok(());
+}
+
+fn update(model: &mut Model, key: KeyCode) {
+    let last = model.events.len().saturating_sub(1);
+    let selected = model.table.selected().unwrap_or(last);
+    match key {
+        KeyCode::Up | KeyCode::Char('k') => {
+            model.follow = false;
+            model.table.select(Some(selected.saturating_sub(1)));
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            model.table.select(Some((selected + 1).min(last)));
+            model.follow = selected + 1 >= last;
+        }
+        KeyCode::End | KeyCode::Char('G') => model.follow = true,
+        KeyCode::Char('q') | KeyCode::Esc => model.quit = true,
+        _ => (),
+    };
+}
+
+fn time_of_day(timestamp: u64) -> String {
+    let formatted = journal::format_timestamp(timestamp);
+    return formatted
+        .split_once(' ')
+        .map_or(formatted.clone(), |(_, time)| time.to_string());
+}
+
+fn view(model: &mut Model, frame: &mut Frame) {
+    let [header_area, table_area, rules_area] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Fill(1),
+        Constraint::Length(8),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from("Window events".bold()),
+            Line::from("↑/↓ scroll, G follow, q quit"),
+        ]),
+        header_area,
+    );
+
+    let rows = model.events.iter().map(|event| {
+        let fired = event.rules.iter().filter(|rule| rule.fired).count();
+        Row::new(vec![
+            time_of_day(event.timestamp),
+            event.event.to_string(),
+            match &event.app.name {
+                Some(name) => format!("{} ({})", event.class, name),
+                None => event.class.clone(),
+            },
+            event.title.clone(),
+            event.detail.clone(),
+            format!("{}/{}", fired, event.rules.len()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(18),
+            Constraint::Fill(1),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Length(5),
+        ],
+    )
+    .header(Row::new(["time", "event", "class", "title", "detail", "rules"]).bold())
+    .row_highlight_style(Style::new().reversed());
+    frame.render_stateful_widget(table, table_area, &mut model.table);
+
+    let rules: Vec<ListItem> = model
+        .table
+        .selected()
+        .and_then(|index| model.events.get(index))
+        .map(|event| {
+            event
+                .rules
+                .iter()
+                .map(|rule| {
+                    let line = format!(
+                        "{} {}:{} {}",
+                        if rule.fired { "fired" } else { "no   " },
+                        rule.file.display(),
+                        rule.line_number,
+                        rule.rule
+                    );
+                    if rule.fired {
+                        return ListItem::new(line).green();
+                    }
+                    return ListItem::new(line).red();
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    frame.render_widget(
+        List::new(rules).block(
+            Block::new()
+                .borders(Borders::TOP)
+                .title("Rules for the window's class"),
+        ),
+        rules_area,
+    );
+}
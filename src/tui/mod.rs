@@ -1,2 +1,5 @@
+pub mod event_table;
 pub mod root;
-pub(self) mod widgets;
+pub mod rule_list;
+pub mod terminal;
+mod widgets;
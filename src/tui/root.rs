@@ -1,123 +1,462 @@
+use super::terminal as tui;
+use super::widgets::window_select::{WindowSelect, WindowSelectEntry};
+use crate::app_info;
+use crate::desktop_entry;
+use crate::hyprland_config::{WindowMode, WorkspaceTarget};
+use crate::hyprland_layers::{self, LayerSurface};
+use crate::process_info;
+use crate::snapshot;
 use crate::wayland;
-use crate::wayland::ClientRegion;
 use crate::wayland::State as WlState;
+use crate::wayland::window_selector;
+use crate::wayland::{ClientRegion, MenuKey, OverlayMenu};
 use hyprland::data::*;
-use hyprland::prelude::*;
 use hyprland::shared::Address;
 use ratatui::prelude::*;
 use std::collections::HashMap;
+use std::mem;
+use std::path::Path;
 use wayland_client::EventQueue;
 
 use color_eyre::Result;
-use ratatui::{Frame, widgets::Paragraph};
-
-#[derive(Debug, Default)]
-enum ViewState {
-    #[default]
-    WindowSelect,
-}
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    widgets::Paragraph,
+};
 
 #[derive(Debug, Default)]
 struct Model {
+    prompt: &'static str,
     selected_window: String,
-    running_state: RunningState,
-    view: ViewState,
+    hovered: Option<Address>,
+    entries: Vec<WindowSelectEntry>,
     wl_state: WlState,
+    /// The window the overlay menu was opened for
+    menu_window: Option<Address>,
+    menu_entries: Vec<(String, MenuEntry)>,
+    workspaces: Vec<WorkspaceTarget>,
+    chosen: Option<WindowMode>,
+}
+
+#[derive(Debug, Clone)]
+enum MenuEntry {
+    Mode(WindowMode),
+    /// Opens a submenu with the open workspaces
+    Workspaces,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
-enum RunningState {
-    #[default]
-    Running,
-    Done,
+#[derive(Debug, Clone)]
+pub struct SelectedWindow {
+    pub client: Client,
+    /// The monitor the window is on
+    pub monitor: Option<Monitor>,
 }
 
-#[derive(PartialEq)]
-enum Message {
-    Select,
-    Quit,
-    //window_selector::create_window();
+#[derive(Debug, Clone)]
+pub enum Selection {
+    Window(Box<SelectedWindow>),
+    /// Several windows picked with shift-click or space
+    Windows(Vec<SelectedWindow>),
+    Layer(LayerSurface),
 }
 
-pub fn tui_root() -> Result<Option<Client>> {
+/// Lets the user pick a window, or a layer surface when `include_layers` is set
+pub fn tui_root(include_layers: bool) -> Result<Option<Selection>> {
     tui::install_panic_hook();
     color_eyre::install()?;
-    let client_result = app();
+    let client_result = app(include_layers);
 
     tui::restore_terminal()?;
     return client_result;
 }
 
-fn index_monitors_by_client_id(monitors: Monitors, clients: Clients) -> HashMap<Address, Monitor> {
+/// Shows the overlay without any windows to pick and returns the monitor that got clicked
+pub fn pick_monitor() -> Result<Option<Monitor>> {
+    tui::install_panic_hook();
+    color_eyre::install()?;
+    let monitor_result = pick_monitor_app();
+
+    tui::restore_terminal()?;
+    return monitor_result;
+}
+
+fn pick_monitor_app() -> Result<Option<Monitor>> {
+    let monitors = snapshot::get_monitors()?;
+    let (wl_state, client_regions) =
+        wayland::window_selector::create_state_and_region_bounds(&[], &monitors, &[]);
+    let event_queue = wayland::window_selector::create_wayland_window_select();
+    let terminal = tui::init_terminal_unless_headless()?;
+
+    let model = render(
+        Model {
+            prompt: "Click a monitor",
+            wl_state,
+            ..Default::default()
+        },
+        client_regions,
+        event_queue,
+        terminal,
+    )?;
+
+    // escape closes the overlay without a click
+    if !model.wl_state.clicked {
+        return Ok(None);
+    }
+    let Some((monitor_id, _)) = model.wl_state.pointer_surface else {
+        return Ok(None);
+    };
+    return Ok(monitors
+        .into_iter()
+        .find(|monitor| monitor.id.to_string() == monitor_id));
+}
+
+fn index_monitors_by_client_id(
+    monitors: &[Monitor],
+    clients: &[Client],
+) -> HashMap<Address, Monitor> {
     return HashMap::<Address, Monitor>::from_iter(clients.iter().filter_map(|client| {
-        match monitors.iter().find(|monitor| {
-            client
-                .monitor
-                .is_some_and(|client_monitor_id| monitor.id == client_monitor_id)
-        }) {
-            Some(monitor) => Some((client.address.clone(), monitor.clone())),
-            None => None,
-        }
+        monitors
+            .iter()
+            .find(|monitor| {
+                client
+                    .monitor
+                    .is_some_and(|client_monitor_id| monitor.id == client_monitor_id)
+            })
+            .map(|monitor| (client.address.clone(), monitor.clone()))
     }));
 }
-fn index_client_id(clients: &Clients) -> HashMap<Address, Client> {
-    return HashMap::<Address, Client>::from_iter(
-        clients
-            .iter()
-            .map(|client| (client.address.clone(), client.clone())),
-    );
+fn select_entries(
+    client_regions: &[ClientRegion],
+    clients: &[Client],
+    layers: &[LayerSurface],
+) -> Vec<WindowSelectEntry> {
+    let desktop_entries = desktop_entry::read_desktop_entries(&desktop_entry::data_dirs());
+    return client_regions
+        .iter()
+        .filter_map(|region| {
+            if let Some(client) = clients
+                .iter()
+                .find(|client| client.address == region.client_id)
+            {
+                let app = app_info::resolve_app(
+                    Path::new(process_info::PROC_DIR),
+                    &desktop_entries,
+                    client,
+                );
+                let label = match app.summary() {
+                    Some(summary) => format!("{}: {}  ({})", client.class, client.title, summary),
+                    None => format!("{}: {}", client.class, client.title),
+                };
+                return Some(WindowSelectEntry {
+                    id: region.client_id.clone(),
+                    label,
+                });
+            }
+            let layer_surface = layers
+                .iter()
+                .find(|layer_surface| layer_surface.layer.address == region.client_id)?;
+            return Some(WindowSelectEntry {
+                id: region.client_id.clone(),
+                label: format!("layer: {}", layer_surface.layer.namespace),
+            });
+        })
+        .collect();
 }
 
-fn app() -> Result<Option<Client>> {
-    let monitors = Monitors::get()?;
-    let clients = Clients::get()?;
+fn app(include_layers: bool) -> Result<Option<Selection>> {
+    let monitors = snapshot::get_monitors()?;
+    let clients = snapshot::get_clients()?;
+    let layers = if include_layers {
+        hyprland_layers::get_layer_surfaces()?
+    } else {
+        vec![]
+    };
 
-    let window_select_inputs =
-        wayland::window_selector::create_state_and_region_bounds(&clients, &monitors);
+    let (wl_state, client_regions) =
+        wayland::window_selector::create_state_and_region_bounds(&clients, &monitors, &layers);
     let event_queue = wayland::window_selector::create_wayland_window_select();
 
-    let mapped_client_id_and_client = index_client_id(&clients);
-    let terminal = tui::init_terminal()?;
+    let entries = select_entries(&client_regions, &clients, &layers);
+    let terminal = tui::init_terminal_unless_headless()?;
 
-    let render_result = render(
-        window_select_inputs,
+    let model = render(
+        Model {
+            prompt: "Select a window (shift-click or space for several, enter when done): ",
+            wl_state,
+            entries,
+            ..Default::default()
+        },
+        client_regions,
         event_queue,
-        mapped_client_id_and_client,
         terminal,
     )?;
 
-    if let Some(click_position) = render_result {
-        println!("Processing...");
-        let selected_client = clients.iter().find(|client| {
-            let click_x = click_position.0.trunc() as i16;
-            let click_y = click_position.1.trunc() as i16;
-            let x = client.at.0 < click_x && click_x < (client.at.0 + client.size.0);
-            let y = client.at.1 < click_y && click_y < (client.at.1 + client.size.1);
-            return x && y;
-        });
-        return Ok(match selected_client {
-            Some(selected_client) => Some(selected_client.clone()),
-            None => None,
+    let mut monitors_by_client_id = index_monitors_by_client_id(&monitors, &clients);
+    let mut selected_window = |address: &Address| {
+        let client = clients
+            .iter()
+            .find(|client| client.address == *address)
+            .cloned()?;
+        return Some(SelectedWindow {
+            monitor: monitors_by_client_id.remove(&client.address),
+            client,
         });
+    };
+
+    // escape, or the overlay went away
+    if !model.wl_state.clicked && !model.wl_state.confirmed {
+        return Ok(None);
+    }
+    let mut picked = model.wl_state.selected.clone();
+    // a click adds the clicked window, enter only falls back to the hovered one when nothing is picked
+    if let Some(hovered) = &model.hovered
+        && !picked.contains(hovered)
+        && (model.wl_state.clicked || picked.is_empty())
+    {
+        picked.push(hovered.clone());
+    }
+    // layers can't be part of a combined rule
+    let mut windows: Vec<SelectedWindow> = picked.iter().filter_map(&mut selected_window).collect();
+    if windows.len() > 1 {
+        println!("Processing...");
+        return Ok(Some(Selection::Windows(windows)));
+    }
+    if let Some(window) = windows.pop() {
+        println!("Processing...");
+        return Ok(Some(Selection::Window(Box::new(window))));
+    }
+
+    let Some(layer_surface) = picked.iter().find_map(|address| {
+        layers
+            .iter()
+            .find(|layer_surface| layer_surface.layer.address == *address)
+    }) else {
+        return Ok(None);
+    };
+    println!("Processing...");
+    return Ok(Some(Selection::Layer(layer_surface.clone())));
+}
+
+/// Lets the user click a window and pick what should happen to it from a menu drawn next to it
+pub fn tui_root_menu() -> Result<Option<(SelectedWindow, WindowMode)>> {
+    tui::install_panic_hook();
+    color_eyre::install()?;
+    let menu_result = menu_app();
+
+    tui::restore_terminal()?;
+    return menu_result;
+}
+
+fn menu_app() -> Result<Option<(SelectedWindow, WindowMode)>> {
+    let monitors = snapshot::get_monitors()?;
+    let clients = snapshot::get_clients()?;
+    let mut workspaces = snapshot::get_workspaces()?;
+    // special workspaces have negative ids, they go last
+    workspaces.sort_by_key(|workspace| (workspace.id < 0, workspace.id));
+
+    let (mut wl_state, client_regions) =
+        wayland::window_selector::create_state_and_region_bounds(&clients, &monitors, &[]);
+    wl_state.menu_enabled = true;
+    let event_queue = wayland::window_selector::create_wayland_window_select();
+
+    let entries = select_entries(&client_regions, &clients, &[]);
+    let terminal = tui::init_terminal_unless_headless()?;
+
+    let model = render(
+        Model {
+            prompt: "Click a window: ",
+            wl_state,
+            entries,
+            workspaces: workspaces
+                .iter()
+                .map(|workspace| {
+                    WorkspaceTarget::from(&WorkspaceBasic {
+                        id: workspace.id,
+                        name: workspace.name.clone(),
+                    })
+                })
+                .collect(),
+            ..Default::default()
+        },
+        client_regions,
+        event_queue,
+        terminal,
+    )?;
+
+    let (Some(address), Some(mode)) = (model.menu_window, model.chosen) else {
+        return Ok(None);
+    };
+    let Some(client) = clients.iter().find(|client| client.address == address) else {
+        return Ok(None);
+    };
+    let mut monitors_by_client_id = index_monitors_by_client_id(&monitors, &clients);
+    return Ok(Some((
+        SelectedWindow {
+            monitor: monitors_by_client_id.remove(&client.address),
+            client: client.clone(),
+        },
+        mode,
+    )));
+}
+
+fn top_menu() -> Vec<(String, MenuEntry)> {
+    return [
+        ("Float", MenuEntry::Mode(WindowMode::Float)),
+        ("Tile", MenuEntry::Mode(WindowMode::Tile)),
+        ("Pin", MenuEntry::Mode(WindowMode::Pin)),
+        ("Fullscreen", MenuEntry::Mode(WindowMode::Fullscreen)),
+        ("Remember size", MenuEntry::Mode(WindowMode::PersistentSize)),
+        ("Send to workspace...", MenuEntry::Workspaces),
+    ]
+    .into_iter()
+    .map(|(label, entry)| (label.to_string(), entry))
+    .collect();
+}
+
+fn workspace_menu(workspaces: &[WorkspaceTarget]) -> Vec<(String, MenuEntry)> {
+    return workspaces
+        .iter()
+        .map(|workspace| {
+            (
+                format!("Workspace {}", workspace),
+                MenuEntry::Mode(WindowMode::Workspace(workspace.clone(), false)),
+            )
+        })
+        .collect();
+}
+
+fn menu_labels(entries: &[(String, MenuEntry)]) -> Vec<String> {
+    return entries.iter().map(|(label, _)| label.clone()).collect();
+}
+
+fn open_menu(model: &mut Model, monitor_id: String, pointer: (f64, f64)) {
+    let (Some(hovered), Some(size)) = (
+        model.hovered.clone(),
+        window_selector::surface_size(&model.wl_state, &monitor_id),
+    ) else {
+        return;
+    };
+    model.menu_entries = top_menu();
+    model.menu_window = Some(hovered);
+    model.wl_state.menu = Some(OverlayMenu::new(
+        monitor_id,
+        pointer,
+        size,
+        menu_labels(&model.menu_entries),
+    ));
+}
+
+fn menu_monitor_id(model: &Model) -> Option<String> {
+    return model
+        .wl_state
+        .menu
+        .as_ref()
+        .map(|menu| menu.monitor_id.clone());
+}
+
+fn close_menu(model: &mut Model) {
+    model.wl_state.menu = None;
+    model.menu_window = None;
+}
+
+fn choose_menu_item(model: &mut Model, index: usize) {
+    let Some((_, entry)) = model.menu_entries.get(index).cloned() else {
+        return;
+    };
+    match entry {
+        MenuEntry::Mode(mode) => {
+            model.chosen = Some(mode);
+            model.wl_state.running = false;
+        }
+        MenuEntry::Workspaces => {
+            model.menu_entries = workspace_menu(&model.workspaces);
+            let labels = menu_labels(&model.menu_entries);
+            let size =
+                model.wl_state.menu.as_ref().and_then(|menu| {
+                    window_selector::surface_size(&model.wl_state, &menu.monitor_id)
+                });
+            if let (Some(menu), Some(size)) = (&mut model.wl_state.menu, size) {
+                menu.replace_items(labels, size);
+            }
+        }
+    };
+}
+
+/// Opens, moves through and picks from the overlay menu, returns whether it has to be drawn again
+fn update_menu(model: &mut Model) -> bool {
+    let pointer = model.wl_state.pointer_position.zip(
+        model
+            .wl_state
+            .pointer_surface
+            .as_ref()
+            .map(|(monitor_id, _)| monitor_id.clone()),
+    );
+    let mut changed = false;
+
+    if let (Some(menu), Some((position, monitor_id))) = (&mut model.wl_state.menu, &pointer)
+        && let Some(index) = menu.item_at(monitor_id, *position)
+        && index != menu.highlighted
+    {
+        menu.highlighted = index;
+        changed = true;
+    }
+
+    if mem::take(&mut model.wl_state.menu_click)
+        && let Some((position, monitor_id)) = pointer
+    {
+        match model
+            .wl_state
+            .menu
+            .as_ref()
+            .map(|menu| menu.item_at(&monitor_id, position))
+        {
+            Some(Some(index)) => choose_menu_item(model, index),
+            // clicking anywhere else closes it
+            Some(None) => close_menu(model),
+            None => open_menu(model, monitor_id, position),
+        };
+        changed = true;
     }
-    return Ok(None);
+
+    if let Some(key) = model.wl_state.menu_key.take()
+        && let Some(menu) = &mut model.wl_state.menu
+    {
+        match key {
+            MenuKey::Up => menu.move_highlight(-1),
+            MenuKey::Down => menu.move_highlight(1),
+            MenuKey::Choose => {
+                let index = menu.highlighted;
+                choose_menu_item(model, index);
+            }
+            MenuKey::Close => close_menu(model),
+        };
+        changed = true;
+    }
+
+    return changed;
 }
 
 fn view(model: &mut Model, frame: &mut Frame) {
     // println!("Press <ESC> to quit.");
-    let span1 = "Select a window: ".bold();
-    let span2 = format!("{}", model.selected_window).bold();
+    let span1 = model.prompt.bold();
+    let span2 = model.selected_window.to_string().bold();
     let line = Line::from(vec![span1, span2]);
     let text = Text::from(line);
-    frame.render_widget(Paragraph::new(text), frame.area());
+    let [header, list] =
+        Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(frame.area());
+    frame.render_widget(Paragraph::new(text), header);
+    frame.render_widget(
+        WindowSelect {
+            entries: &model.entries,
+            hovered: model.hovered.as_ref(),
+            selected: &model.wl_state.selected,
+        },
+        list,
+    );
 }
 
-fn update(
-    model: &mut Model,
-    msg: String,
-    client_regions: Vec<ClientRegion>,
-    mapped_client_id_and_client: HashMap<Address, Client>,
-) -> Option<Message> {
+fn update(model: &mut Model, msg: String, client_regions: Vec<ClientRegion>) {
     model.selected_window = msg;
     if let (Some(pointer_position), Some((pointer_monitor_id, _))) = (
         model.wl_state.pointer_position,
@@ -130,89 +469,77 @@ fn update(
             let x = client.at.0 < pointer_x && pointer_x < (client.at.0 + client.size.0);
             let y = client.at.1 < pointer_y && pointer_y < (client.at.1 + client.size.1);
             if let Some(client_monitor) = &client.monitor {
-                return x && y && client_monitor.to_string() == pointer_monitor_id;
+                return x && y && *client_monitor == pointer_monitor_id;
             }
             return false;
         });
-        let hovered_client = match hovered_client_region {
-            Some(hovered_client_region) => {
-                mapped_client_id_and_client.get(&hovered_client_region.client_id)
-            }
-            None => None,
-        };
-        model.selected_window = match hovered_client {
-            Some(client) => client.title.clone(),
+        model.hovered = hovered_client_region.map(|region| region.client_id.clone());
+        model.selected_window = match model
+            .entries
+            .iter()
+            .find(|entry| Some(&entry.id) == model.hovered.as_ref())
+        {
+            Some(entry) => entry.label.clone(),
             None => "".to_string(),
         };
     }
-    /* match msg {
-        Message::Select => {}
-        Message::Quit => {
-            // You can handle cleanup and exit here
-            model.running_state = RunningState::Done;
+}
+
+fn toggle_hovered(model: &mut Model) {
+    let Some(hovered) = model.hovered.clone() else {
+        return;
+    };
+    match model
+        .wl_state
+        .selected
+        .iter()
+        .position(|address| *address == hovered)
+    {
+        Some(index) => {
+            model.wl_state.selected.remove(index);
         }
-    }; */
-    None
+        None => model.wl_state.selected.push(hovered),
+    };
 }
 
 fn render(
-    (state, client_regions): (WlState, Vec<ClientRegion>),
+    mut model: Model,
+    client_regions: Vec<ClientRegion>,
     mut event_queue: EventQueue<WlState>,
-    mapped_client_id_and_client: HashMap<Address, Client>,
-    mut terminal: Terminal<impl Backend>,
-) -> Result<Option<(f64, f64)>> {
-    let mut model = Model::default();
-    model.wl_state = state;
+    mut terminal: Option<Terminal<impl Backend>>,
+) -> Result<Model> {
+    let qh = event_queue.handle();
     while model.wl_state.running {
         event_queue
             .blocking_dispatch(&mut model.wl_state)
             .expect("window loop");
 
-        update(
-            &mut model,
-            "None".to_string(),
-            client_regions.clone(),
-            mapped_client_id_and_client.clone(),
-        );
-
-        terminal.draw(|f| view(&mut model, f))?;
-    }
-
-    Ok(model.wl_state.pointer_position.clone())
-}
-
-mod tui {
-    use ratatui::{
-        Terminal,
-        backend::{Backend, CrosstermBackend},
-        crossterm::{
-            ExecutableCommand,
-            terminal::{
-                EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
-            },
-        },
-    };
-    use std::{io::stdout, panic};
-
-    pub fn init_terminal() -> color_eyre::Result<Terminal<impl Backend>> {
-        enable_raw_mode()?;
-        stdout().execute(EnterAlternateScreen)?;
-        let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-        Ok(terminal)
-    }
+        update(&mut model, "None".to_string(), client_regions.clone());
+        if model.wl_state.menu_enabled {
+            let menu_monitor = menu_monitor_id(&model);
+            if update_menu(&mut model) {
+                // only the monitors the menu was and is on look any different
+                let mut monitor_ids: Vec<String> = menu_monitor
+                    .into_iter()
+                    .chain(menu_monitor_id(&model))
+                    .collect();
+                monitor_ids.dedup();
+                for monitor_id in monitor_ids {
+                    window_selector::redraw(&mut model.wl_state, &qh, &monitor_id);
+                }
+            }
+        } else if model.wl_state.toggle_requested {
+            model.wl_state.toggle_requested = false;
+            toggle_hovered(&mut model);
+            if let Some((monitor_id, _)) = model.wl_state.pointer_surface.clone() {
+                window_selector::redraw(&mut model.wl_state, &qh, &monitor_id);
+            }
+        }
 
-    pub fn restore_terminal() -> color_eyre::Result<()> {
-        stdout().execute(LeaveAlternateScreen)?;
-        disable_raw_mode()?;
-        Ok(())
+        if let Some(terminal) = &mut terminal {
+            terminal.draw(|f| view(&mut model, f))?;
+        }
     }
 
-    pub fn install_panic_hook() {
-        let original_hook = panic::take_hook();
-        panic::set_hook(Box::new(move |panic_info| {
-            stdout().execute(LeaveAlternateScreen).unwrap();
-            disable_raw_mode().unwrap();
-            original_hook(panic_info);
-        }));
-    }
+    Ok(model)
 }
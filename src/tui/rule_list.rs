@@ -0,0 +1,211 @@
+use super::terminal;
+use super::widgets::rule_list::{PendingEdit, RuleList};
+use crate::rules_file::{LineEdit, RuleLine};
+
+use color_eyre::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::prelude::*;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    widgets::Paragraph,
+};
+
+/// What enter does on the highlighted rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleAction {
+    Remove,
+    Edit,
+}
+
+#[derive(Debug, Default)]
+struct LineInput {
+    text: String,
+    /// Char index, not byte index
+    cursor: usize,
+}
+
+impl LineInput {
+    fn byte_index(&self) -> usize {
+        return self
+            .text
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(index, _)| index)
+            .unwrap_or(self.text.len());
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RunningState {
+    Running,
+    Save,
+    Quit,
+}
+
+struct Model {
+    selected: String,
+    action: RuleAction,
+    rules: Vec<RuleLine>,
+    pending: Vec<Option<PendingEdit>>,
+    cursor: usize,
+    input: Option<LineInput>,
+    running_state: RunningState,
+}
+
+/// Lists `rules` and lets the user mark them for removal or rewrite them,
+/// nothing is returned when they quit without saving
+pub fn pick_rule_edits(
+    selected: &str,
+    rules: Vec<RuleLine>,
+    action: RuleAction,
+) -> Result<Vec<LineEdit>> {
+    terminal::install_panic_hook();
+    let mut model = Model {
+        selected: selected.to_string(),
+        action,
+        pending: vec![None; rules.len()],
+        rules,
+        cursor: 0,
+        input: None,
+        running_state: RunningState::Running,
+    };
+    let result = run(&mut model);
+    terminal::restore_terminal()?;
+    result?;
+
+    if model.running_state != RunningState::Save {
+        return Ok(vec![]);
+    }
+    return Ok(model
+        .rules
+        .into_iter()
+        .zip(model.pending)
+        .filter_map(|(rule, pending)| match pending? {
+            PendingEdit::Remove => Some(LineEdit::Remove(rule)),
+            PendingEdit::Replace(text) => Some(LineEdit::Replace(rule, text)),
+        })
+        .collect());
+}
+
+fn run(model: &mut Model) -> Result<()> {
+    let mut terminal = terminal::init_terminal()?;
+    while model.running_state == RunningState::Running {
+        terminal.draw(|f| view(model, f))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            update(model, key.code);
+        }
+    }
+    return Ok(());
+}
+
+fn start_editing(model: &mut Model) {
+    let text = match &model.pending[model.cursor] {
+        Some(PendingEdit::Replace(text)) => text.clone(),
+        _ => model.rules[model.cursor].text.clone(),
+    };
+    model.input = Some(LineInput {
+        cursor: text.chars().count(),
+        text,
+    });
+}
+
+fn toggle_remove(model: &mut Model) {
+    let pending = &mut model.pending[model.cursor];
+    *pending = match pending {
+        Some(PendingEdit::Remove) => None,
+        _ => Some(PendingEdit::Remove),
+    };
+}
+
+fn update(model: &mut Model, key: KeyCode) {
+    if let Some(input) = model.input.as_mut() {
+        match key {
+            KeyCode::Char(char) => {
+                let index = input.byte_index();
+                input.text.insert(index, char);
+                input.cursor += 1;
+            }
+            KeyCode::Backspace if input.cursor > 0 => {
+                input.cursor -= 1;
+                let index = input.byte_index();
+                input.text.remove(index);
+            }
+            KeyCode::Delete if input.cursor < input.text.chars().count() => {
+                let index = input.byte_index();
+                input.text.remove(index);
+            }
+            KeyCode::Left => input.cursor = input.cursor.saturating_sub(1),
+            KeyCode::Right => input.cursor = (input.cursor + 1).min(input.text.chars().count()),
+            KeyCode::Home => input.cursor = 0,
+            KeyCode::End => input.cursor = input.text.chars().count(),
+            KeyCode::Enter => {
+                let text = input.text.clone();
+                model.pending[model.cursor] = if text == model.rules[model.cursor].text {
+                    None
+                } else {
+                    Some(PendingEdit::Replace(text))
+                };
+                model.input = None;
+            }
+            KeyCode::Esc => model.input = None,
+            _ => (),
+        };
+        return;
+    }
+
+    match key {
+        KeyCode::Up | KeyCode::Char('k') => model.cursor = model.cursor.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => {
+            model.cursor = (model.cursor + 1).min(model.rules.len().saturating_sub(1))
+        }
+        KeyCode::Char('d') | KeyCode::Delete => toggle_remove(model),
+        KeyCode::Char('e') => start_editing(model),
+        KeyCode::Enter => match model.action {
+            RuleAction::Remove => toggle_remove(model),
+            RuleAction::Edit => start_editing(model),
+        },
+        KeyCode::Char('w') => model.running_state = RunningState::Save,
+        KeyCode::Char('q') | KeyCode::Esc => model.running_state = RunningState::Quit,
+        _ => (),
+    };
+}
+
+fn view(model: &mut Model, frame: &mut Frame) {
+    let header = Text::from(vec![
+        Line::from(vec![
+            "Rules matching: ".bold(),
+            model.selected.clone().bold(),
+        ]),
+        Line::from("↑/↓ move, d remove, e edit, w save, q quit"),
+    ]);
+    let [header_area, list_area, input_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    frame.render_widget(Paragraph::new(header), header_area);
+    frame.render_widget(
+        RuleList {
+            rules: &model.rules,
+            pending: &model.pending,
+            cursor: model.cursor,
+        },
+        list_area,
+    );
+
+    if let Some(input) = &model.input {
+        let prompt = "> ";
+        frame.render_widget(
+            Paragraph::new(format!("{}{}", prompt, input.text)),
+            input_area,
+        );
+        frame.set_cursor_position((
+            input_area.x + (prompt.len() + input.cursor) as u16,
+            input_area.y,
+        ));
+    }
+}
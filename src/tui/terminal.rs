@@ -0,0 +1,53 @@
+use ratatui::{
+    Terminal,
+    backend::{Backend, CrosstermBackend},
+    crossterm::{
+        ExecutableCommand,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+};
+use std::{io::stdout, panic, sync::OnceLock};
+
+/// Set from `--headless`, the overlay is shown without the list in the terminal
+static HEADLESS: OnceLock<bool> = OnceLock::new();
+
+pub fn set_headless(headless: bool) {
+    let _ = HEADLESS.set(headless);
+}
+
+pub fn is_headless() -> bool {
+    return HEADLESS.get().copied().unwrap_or(false);
+}
+
+pub fn init_terminal() -> color_eyre::Result<Terminal<impl Backend>> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    Ok(terminal)
+}
+
+/// `None` in headless mode, there's no terminal to draw in when started from a keybind
+pub fn init_terminal_unless_headless() -> color_eyre::Result<Option<Terminal<impl Backend>>> {
+    if is_headless() {
+        return Ok(None);
+    }
+    return Ok(Some(init_terminal()?));
+}
+
+pub fn restore_terminal() -> color_eyre::Result<()> {
+    if is_headless() {
+        return Ok(());
+    }
+    stdout().execute(LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    Ok(())
+}
+
+pub fn install_panic_hook() {
+    let original_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        stdout().execute(LeaveAlternateScreen).unwrap();
+        disable_raw_mode().unwrap();
+        original_hook(panic_info);
+    }));
+}
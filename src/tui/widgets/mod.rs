@@ -1 +1,2 @@
+pub mod rule_list;
 pub mod window_select;
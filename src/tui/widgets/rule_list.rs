@@ -0,0 +1,47 @@
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem},
+};
+
+use crate::rules_file::RuleLine;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingEdit {
+    Remove,
+    Replace(String),
+}
+
+/// Rules from the rules file with what's going to happen to them once the changes are saved
+pub struct RuleList<'a> {
+    pub rules: &'a [RuleLine],
+    pub pending: &'a [Option<PendingEdit>],
+    pub cursor: usize,
+}
+
+impl Widget for RuleList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let items =
+            self.rules
+                .iter()
+                .zip(self.pending)
+                .enumerate()
+                .map(|(index, (rule, pending))| {
+                    let item = match pending {
+                        None => ListItem::new(format!("  {:>4} {}", rule.line_number, rule.text)),
+                        Some(PendingEdit::Remove) => {
+                            ListItem::new(format!("- {:>4} {}", rule.line_number, rule.text))
+                                .red()
+                                .crossed_out()
+                        }
+                        Some(PendingEdit::Replace(text)) => {
+                            ListItem::new(format!("~ {:>4} {}", rule.line_number, text)).yellow()
+                        }
+                    };
+                    if index == self.cursor {
+                        return item.reversed();
+                    }
+                    return item;
+                });
+        Widget::render(List::new(items), area, buf);
+    }
+}
@@ -1,5 +1,36 @@
-/* pub trait WindowSelect {
-    type State;
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State);
+use hyprland::shared::Address;
+use ratatui::{
+    prelude::*,
+    widgets::{List, ListItem},
+};
+
+#[derive(Debug, Clone)]
+pub struct WindowSelectEntry {
+    pub id: Address,
+    pub label: String,
+}
+
+/// Everything that can be picked in the overlay, the entry under the cursor is highlighted and
+/// the ones picked so far with shift-click or space are marked
+pub struct WindowSelect<'a> {
+    pub entries: &'a [WindowSelectEntry],
+    pub hovered: Option<&'a Address>,
+    pub selected: &'a [Address],
+}
+
+impl Widget for WindowSelect<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let items = self.entries.iter().map(|entry| {
+            let item = if self.selected.contains(&entry.id) {
+                ListItem::new(format!("+ {}", entry.label)).green()
+            } else {
+                ListItem::new(entry.label.clone())
+            };
+            if Some(&entry.id) == self.hovered {
+                return item.reversed();
+            }
+            return item;
+        });
+        Widget::render(List::new(items), area, buf);
+    }
 }
- */
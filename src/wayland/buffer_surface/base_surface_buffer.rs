@@ -1,5 +1,3 @@
-use wayland_client::protocol::wl_output;
-
 use crate::wayland::ClientRegion;
 
 #[derive(Debug, Clone)]
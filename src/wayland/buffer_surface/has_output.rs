@@ -1,3 +1,4 @@
+use crate::hyprland_layers::SELECTOR_NAMESPACE;
 use crate::wayland::ClientRegion;
 
 use super::super::protocols::State;
@@ -17,7 +18,6 @@ use wayland_protocols_wlr::layer_shell::v1::client::{
 #[derive(Debug, Clone)]
 pub struct HasOutput {
     pub monitor_id: String,
-    pub wayland_output: wl_output::WlOutput,
     pub size: (u16, u16),
     pub buffer: wl_buffer::WlBuffer,
     pub base_surface: wl_surface::WlSurface,
@@ -54,8 +54,7 @@ impl
             size: in_process.size,
             buffer: in_process.buffer,
             base_surface: in_process.base_surface,
-            wlr_surface: wlr_surface,
-            wayland_output: output.clone(),
+            wlr_surface,
             monitor_clients: in_process.monitor_clients,
         };
         has_output.init_wlr_surface();
@@ -85,7 +84,7 @@ fn create_layer_surface(
         base_surface,
         Some(output),
         Layer::Top,
-        "selection".to_string(),
+        SELECTOR_NAMESPACE.to_string(),
         qh,
         Some(monitor_id),
     );
@@ -38,7 +38,7 @@ impl
         InProcess {
             monitor_id: pre.monitor_id.clone(),
             size: pre.monitor_size,
-            buffer: create_minimal_surface_buffer(&shm, qh),
+            buffer: create_minimal_surface_buffer(shm, qh),
             base_surface: create_base_surface(compositor, qh),
             monitor_clients: pre.monitor_clients,
         }
@@ -55,17 +55,17 @@ fn create_minimal_surface_buffer(
     let mut buf = std::io::BufWriter::new(&file);
 
     for _ in 0..(init_w * init_h) {
-        buf.write_all(&[0x00 as u8, 0x00 as u8, 0x00 as u8, 0x00 as u8])
+        buf.write_all(&[0x00_u8, 0x00_u8, 0x00_u8, 0x00_u8])
             .unwrap();
     }
     buf.flush().unwrap();
 
-    let pool = shm.create_pool(file.as_fd(), init_w as i32 * init_h as i32 * 4, qh, ());
+    let pool = shm.create_pool(file.as_fd(), init_w * init_h * 4, qh, ());
     let buffer = pool.create_buffer(
         0,
-        init_w as i32,
-        init_h as i32,
-        (init_w * 4) as i32,
+        init_w,
+        init_h,
+        init_w * 4,
         wl_shm::Format::Argb8888,
         qh,
         (),
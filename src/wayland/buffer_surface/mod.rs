@@ -10,6 +10,8 @@ pub(super) use in_process::InProcess;
 pub(super) use ready_to_draw::ReadyToDraw;
 pub use types::ClientRegion;
 
+use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::KeyboardInteractivity;
+
 //pub(crate) use self::help_template::HelpTemplate;
 
 #[derive(Debug, Clone)]
@@ -19,3 +21,22 @@ pub enum BufferSurface {
     HasOutput(HasOutput),
     ReadyToDraw(ReadyToDraw),
 }
+
+impl BufferSurface {
+    /// Gives the overlay the keyboard for escape, space and enter, or takes it away again. Only one
+    /// overlay should have it, the others would fight over it.
+    pub fn set_keyboard_focus(&self, focused: bool) {
+        let (wlr_surface, base_surface) = match self {
+            Self::HasOutput(has_output) => (&has_output.wlr_surface, &has_output.base_surface),
+            Self::ReadyToDraw(ready_to_draw) => {
+                (&ready_to_draw.wlr_surface, &ready_to_draw.base_surface)
+            }
+            Self::Pre(_) | Self::InProcess(_) => return,
+        };
+        wlr_surface.set_keyboard_interactivity(match focused {
+            true => KeyboardInteractivity::Exclusive,
+            false => KeyboardInteractivity::None,
+        });
+        base_surface.commit();
+    }
+}
@@ -1,31 +1,24 @@
 use crate::wayland::ClientRegion;
+use crate::wayland::OverlayMenu;
+use hyprland::shared::Address;
 
 use super::super::protocols::State;
 use super::has_output::HasOutput;
 
-use std::{
-    fs::File,
-    io::Write,
-    os::fd::AsFd,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use std::{fs::File, io::Write, mem, os::fd::AsFd};
 
 use wayland_client::{
     QueueHandle,
     protocol::{
         wl_buffer::{self},
-        wl_output, wl_shm, wl_surface,
+        wl_shm, wl_surface,
     },
 };
-use wayland_protocols_wlr::layer_shell::v1::client::{
-    zwlr_layer_shell_v1::{self, Layer, ZwlrLayerShellV1},
-    zwlr_layer_surface_v1,
-};
+use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1;
 
 #[derive(Debug, Clone)]
 pub struct ReadyToDraw {
     pub monitor_id: String,
-    pub wayland_output: wl_output::WlOutput,
     pub size: (u16, u16),
     pub buffer: wl_buffer::WlBuffer,
     pub base_surface: wl_surface::WlSurface,
@@ -43,7 +36,6 @@ impl From<(HasOutput, &zwlr_layer_surface_v1::ZwlrLayerSurfaceV1)> for ReadyToDr
             buffer: has_output.buffer,
             base_surface: has_output.base_surface,
             wlr_surface: layer_surface.clone(),
-            wayland_output: has_output.wayland_output,
             monitor_clients: has_output.monitor_clients,
         };
 
@@ -56,15 +48,32 @@ impl From<(HasOutput, &zwlr_layer_surface_v1::ZwlrLayerSurfaceV1)> for ReadyToDr
 }
 
 impl ReadyToDraw {
-    pub fn acknowledge_configure(self: &Self, serial: u32) {
+    pub fn acknowledge_configure(&self, serial: u32) {
         self.wlr_surface.ack_configure(serial);
     }
 
-    pub fn actually_draw_buffer_surface(&mut self, shm: &wl_shm::WlShm, qh: &QueueHandle<State>) {
-        self.buffer = create_surface_buffer(&shm, qh, self.monitor_clients.clone(), self.size);
+    /// `menu` is only drawn when it's on this surface's monitor, the `selected` windows get their own colour
+    pub fn actually_draw_buffer_surface(
+        &mut self,
+        shm: &wl_shm::WlShm,
+        qh: &QueueHandle<State>,
+        menu: Option<&OverlayMenu>,
+        selected: &[Address],
+    ) {
+        let menu = menu.filter(|menu| menu.monitor_id == self.monitor_id);
+        let buffer = create_surface_buffer(
+            shm,
+            qh,
+            self.monitor_clients.clone(),
+            self.size,
+            menu,
+            selected,
+        );
         self.base_surface
-            .attach(Some(&self.buffer), self.size.0 as i32, self.size.1 as i32);
+            .attach(Some(&buffer), self.size.0 as i32, self.size.1 as i32);
         self.base_surface.commit();
+        // nothing is attached to the old one anymore once the new one is committed
+        mem::replace(&mut self.buffer, buffer).destroy();
     }
 }
 
@@ -73,12 +82,20 @@ fn create_surface_buffer(
     qh: &QueueHandle<State>,
     monitor_clients: Vec<ClientRegion>,
     size: (u16, u16),
+    menu: Option<&OverlayMenu>,
+    selected: &[Address],
 ) -> wl_buffer::WlBuffer {
     let (init_w, init_h) = size;
 
     let mut file = tempfile::tempfile().unwrap();
 
-    draw(&mut file, (init_w as i16, init_h as i16), monitor_clients);
+    draw(
+        &mut file,
+        (init_w as i16, init_h as i16),
+        monitor_clients,
+        menu,
+        selected,
+    );
     let pool = shm.create_pool(file.as_fd(), init_w as i32 * init_h as i32 * 4, qh, ());
     let buffer = pool.create_buffer(
         0,
@@ -89,10 +106,14 @@ fn create_surface_buffer(
         qh,
         (),
     );
-    return buffer.clone();
+    // the memory stays around until the buffer made from it is destroyed
+    pool.destroy();
+    return buffer;
 }
-const BG_COLOR: [u8; 4] = [0x00 as u8, 0x00 as u8, 0x00 as u8, 0x00 as u8];
-const FG_COLOR: [u8; 4] = [0x40 as u8, 0x40 as u8, 0x40 as u8, 0x2F as u8];
+const BG_COLOR: [u8; 4] = [0x00_u8, 0x00_u8, 0x00_u8, 0x00_u8];
+const FG_COLOR: [u8; 4] = [0x40_u8, 0x40_u8, 0x40_u8, 0x2F_u8];
+/// Same blue as the menu highlight, see the note on the colours in `overlay_menu`
+const SELECTED_COLOR: [u8; 4] = [0x58_u8, 0x38_u8, 0x18_u8, 0x60_u8];
 
 fn is_inside_region((x_cord, y_cord): (i16, i16), client: &ClientRegion) -> bool {
     let x = client.at.0 < x_cord && x_cord < (client.at.0 + client.size.0);
@@ -109,23 +130,37 @@ fn is_inside_region((x_cord, y_cord): (i16, i16), client: &ClientRegion) -> bool
     return false;
 }
  */
-fn draw(tmp: &mut File, (buf_x, buf_y): (i16, i16), monitor_clients: Vec<ClientRegion>) {
+fn draw(
+    tmp: &mut File,
+    (buf_x, buf_y): (i16, i16),
+    monitor_clients: Vec<ClientRegion>,
+    menu: Option<&OverlayMenu>,
+    selected: &[Address],
+) {
     /*     let start = SystemTime::now()
     .duration_since(UNIX_EPOCH)
     .expect("time should go forward"); */
-    let mut buf = std::io::BufWriter::new(tmp);
+    // drawn in memory first since the menu goes on top of the windows
+    let mut pixels = Vec::with_capacity(buf_x as usize * buf_y as usize * 4);
     for y in 0..buf_y {
         for x in 0..buf_x {
+            // the first region is the one the pointer picks when windows overlap
             match monitor_clients
                 .iter()
-                .any(|client| is_inside_region((x, y), client))
+                .find(|client| is_inside_region((x, y), client))
             {
-                true => buf.write_all(&FG_COLOR).unwrap(),
-                false => buf.write_all(&BG_COLOR).unwrap(),
+                Some(client) if selected.contains(&client.client_id) => {
+                    pixels.extend_from_slice(&SELECTED_COLOR)
+                }
+                Some(_) => pixels.extend_from_slice(&FG_COLOR),
+                None => pixels.extend_from_slice(&BG_COLOR),
             };
         }
     }
-    buf.flush().unwrap();
+    if let Some(menu) = menu {
+        menu.draw(&mut pixels, buf_x as usize);
+    }
+    tmp.write_all(&pixels).unwrap();
 
     /*     let end = SystemTime::now()
     .duration_since(UNIX_EPOCH)
@@ -0,0 +1,152 @@
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+use color_eyre::eyre;
+use wayland_client::{
+    Connection, Dispatch, QueueHandle, delegate_noop, event_created_child,
+    globals::{GlobalListContents, registry_queue_init},
+    protocol::{wl_registry, wl_seat},
+};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+    zwlr_data_control_source_v1::{self, ZwlrDataControlSourceV1},
+};
+
+/// The hidden subcommand that keeps serving the selection after we exit
+pub const SERVE_COMMAND: &str = "serve-clipboard";
+/// First line the serving process prints once it owns the selection, anything else is an error
+pub const READY: &str = "ready";
+
+const MIME_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+];
+
+struct ClipboardState {
+    text: String,
+    serving: bool,
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for ClipboardState {
+    fn event(
+        _: &mut Self,
+        _: &wl_registry::WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(ClipboardState: ignore wl_seat::WlSeat);
+delegate_noop!(ClipboardState: ZwlrDataControlManagerV1);
+delegate_noop!(ClipboardState: ignore ZwlrDataControlOfferV1);
+
+impl Dispatch<ZwlrDataControlDeviceV1, ()> for ClipboardState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlDeviceV1,
+        _: zwlr_data_control_device_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+
+    // other clients' selections are announced as offers, we only care about losing ours
+    event_created_child!(ClipboardState, ZwlrDataControlDeviceV1, [
+        zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrDataControlSourceV1, ()> for ClipboardState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrDataControlSourceV1,
+        event: zwlr_data_control_source_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_data_control_source_v1::Event::Send { fd, .. } => {
+                // the pasting client going away mid-write isn't our problem
+                let _ = File::from(fd).write_all(state.text.as_bytes());
+            }
+            zwlr_data_control_source_v1::Event::Cancelled => state.serving = false,
+            _ => (),
+        }
+    }
+}
+
+/// Takes over the clipboard with `text` and serves it until something else is copied, `ready` is
+/// called once the selection is ours
+pub fn serve(text: String, ready: impl FnOnce()) -> eyre::Result<()> {
+    let connection = Connection::connect_to_env()?;
+    let (globals, mut event_queue) = registry_queue_init::<ClipboardState>(&connection)?;
+    let qh = event_queue.handle();
+    let manager: ZwlrDataControlManagerV1 = globals
+        .bind(&qh, 1..=1, ())
+        .map_err(|_| eyre::eyre!("The compositor doesn't support wlr-data-control"))?;
+    let seat: wl_seat::WlSeat = globals.bind(&qh, 1..=1, ())?;
+
+    let source = manager.create_data_source(&qh, ());
+    for mime_type in MIME_TYPES {
+        source.offer(mime_type.to_string());
+    }
+    let device = manager.get_data_device(&seat, &qh, ());
+    device.set_selection(Some(&source));
+
+    let mut state = ClipboardState {
+        text,
+        serving: true,
+    };
+    event_queue.roundtrip(&mut state)?;
+    ready();
+    while state.serving {
+        event_queue.blocking_dispatch(&mut state)?;
+    }
+
+    source.destroy();
+    device.destroy();
+    return Ok(());
+}
+
+/// Puts `text` on the clipboard from a copy of ourselves in its own process group, so pasting keeps
+/// working after we exit and closing the terminal doesn't take it down
+pub fn copy(text: &str) -> eyre::Result<()> {
+    let mut server = Command::new(env::current_exe()?)
+        .arg(SERVE_COMMAND)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+    // dropped right away so the server sees the end of the text
+    server
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(text.as_bytes())?;
+
+    let mut status = String::new();
+    BufReader::new(server.stdout.take().expect("stdout is piped")).read_line(&mut status)?;
+    if status.trim() == READY {
+        return Ok(());
+    }
+    server.wait()?;
+    let reason = match status.trim() {
+        "" => "the clipboard process exited early",
+        reason => reason,
+    };
+    return Err(eyre::eyre!("Couldn't copy to the clipboard: {}", reason));
+}
@@ -1,5 +1,9 @@
-pub(self) mod buffer_surface;
-pub(self) mod protocols;
+mod bitmap_font;
+mod buffer_surface;
+pub mod clipboard;
+mod overlay_menu;
+mod protocols;
 pub mod window_selector;
 pub use buffer_surface::ClientRegion;
+pub use overlay_menu::{MenuKey, OverlayMenu};
 pub use protocols::State;
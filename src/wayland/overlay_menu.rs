@@ -0,0 +1,142 @@
+use super::bitmap_font;
+
+const SCALE: usize = 2;
+const PADDING_X: usize = 10;
+const PADDING_Y: usize = 6;
+/// Gap between the pointer and the menu so it doesn't cover what was clicked
+const POINTER_OFFSET: i16 = 8;
+
+// ARGB8888 is stored little endian so these are B, G, R, A
+const MENU_COLOR: [u8; 4] = [0x24, 0x20, 0x20, 0xF0];
+const HIGHLIGHT_COLOR: [u8; 4] = [0xB0, 0x70, 0x30, 0xFF];
+const TEXT_COLOR: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+
+/// Keys that do something while the menu is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuKey {
+    Up,
+    Down,
+    Choose,
+    Close,
+}
+
+/// A list of labels drawn on the overlay of one monitor, picked with the pointer or the keyboard
+#[derive(Debug, Clone)]
+pub struct OverlayMenu {
+    pub monitor_id: String,
+    /// Top left corner relative to the monitor
+    pub at: (i16, i16),
+    pub items: Vec<String>,
+    pub highlighted: usize,
+}
+
+impl OverlayMenu {
+    /// Opens next to `pointer`, moved over when it would go off the edge of a monitor of `monitor_size`
+    pub fn new(
+        monitor_id: String,
+        pointer: (f64, f64),
+        monitor_size: (u16, u16),
+        items: Vec<String>,
+    ) -> Self {
+        let mut menu = Self {
+            monitor_id,
+            at: (0, 0),
+            items,
+            highlighted: 0,
+        };
+        menu.place(pointer, monitor_size);
+        return menu;
+    }
+
+    fn place(&mut self, pointer: (f64, f64), monitor_size: (u16, u16)) {
+        let (width, height) = self.size();
+        let max_x = (monitor_size.0 as i16 - width).max(0);
+        let max_y = (monitor_size.1 as i16 - height).max(0);
+        self.at = (
+            (pointer.0 as i16 + POINTER_OFFSET).clamp(0, max_x),
+            (pointer.1 as i16 + POINTER_OFFSET).clamp(0, max_y),
+        );
+    }
+
+    /// Swaps in the items of a submenu, keeping it where it was as far as the monitor allows
+    pub fn replace_items(&mut self, items: Vec<String>, monitor_size: (u16, u16)) {
+        let pointer = (
+            (self.at.0 - POINTER_OFFSET) as f64,
+            (self.at.1 - POINTER_OFFSET) as f64,
+        );
+        self.items = items;
+        self.highlighted = 0;
+        self.place(pointer, monitor_size);
+    }
+
+    fn item_height() -> i16 {
+        return (bitmap_font::GLYPH_HEIGHT * SCALE + 2 * PADDING_Y) as i16;
+    }
+
+    pub fn size(&self) -> (i16, i16) {
+        let text_width = self
+            .items
+            .iter()
+            .map(|item| bitmap_font::text_width(item, SCALE))
+            .max()
+            .unwrap_or(0);
+        return (
+            (text_width + 2 * PADDING_X) as i16,
+            Self::item_height() * self.items.len() as i16,
+        );
+    }
+
+    /// The item under `pointer` on the monitor with `monitor_id`
+    pub fn item_at(&self, monitor_id: &str, pointer: (f64, f64)) -> Option<usize> {
+        if monitor_id != self.monitor_id {
+            return None;
+        }
+        let (width, height) = self.size();
+        let x = pointer.0 as i16 - self.at.0;
+        let y = pointer.1 as i16 - self.at.1;
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return None;
+        }
+        return Some((y / Self::item_height()) as usize);
+    }
+
+    /// Moves the highlight by `by` items, wrapping around at either end
+    pub fn move_highlight(&mut self, by: isize) {
+        let count = self.items.len() as isize;
+        if count == 0 {
+            return;
+        }
+        self.highlighted = (self.highlighted as isize + by).rem_euclid(count) as usize;
+    }
+
+    /// Draws the menu into an ARGB8888 buffer `width` pixels wide covering the whole monitor
+    pub fn draw(&self, pixels: &mut [u8], width: usize) {
+        let (menu_width, _) = self.size();
+        let item_height = Self::item_height() as usize;
+        let (left, top) = (self.at.0 as usize, self.at.1 as usize);
+        for (index, item) in self.items.iter().enumerate() {
+            let item_top = top + index * item_height;
+            let color = if index == self.highlighted {
+                HIGHLIGHT_COLOR
+            } else {
+                MENU_COLOR
+            };
+            for y in item_top..item_top + item_height {
+                for x in left..(left + menu_width as usize).min(width) {
+                    let offset = (y * width + x) * 4;
+                    if offset + 4 <= pixels.len() {
+                        pixels[offset..offset + 4].copy_from_slice(&color);
+                    }
+                }
+            }
+            bitmap_font::draw_text(
+                pixels,
+                width,
+                (left + PADDING_X, item_top + PADDING_Y),
+                item,
+                SCALE,
+                TEXT_COLOR,
+            );
+        }
+    }
+}
@@ -1,8 +1,23 @@
 use wayland_client::{
+    WEnum,
     protocol::wl_keyboard,
     {Connection, Dispatch, QueueHandle},
 };
 
+use super::super::overlay_menu::MenuKey;
+
+const KEY_ESC: u32 = 1;
+const KEY_ENTER: u32 = 28;
+const KEY_J: u32 = 36;
+const KEY_K: u32 = 37;
+const KEY_SPACE: u32 = 57;
+const KEY_UP: u32 = 103;
+const KEY_DOWN: u32 = 108;
+/// Shift's bit in the modifier masks. It isn't looked up in the keymap the compositor sends:
+/// xkbcommon always puts the eight real modifiers first with Shift at index 0, so this only breaks
+/// with a compositor that builds its keymaps some other way
+const SHIFT_MASK: u32 = 1;
+
 impl Dispatch<wl_keyboard::WlKeyboard, ()> for super::State {
     fn event(
         state: &mut Self,
@@ -12,11 +27,37 @@ impl Dispatch<wl_keyboard::WlKeyboard, ()> for super::State {
         _: &Connection,
         _: &QueueHandle<Self>,
     ) {
-        if let wl_keyboard::Event::Key { key, .. } = event {
-            if key == 1 {
-                // ESC key
-                state.running = false;
+        match event {
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(wl_keyboard::KeyState::Pressed),
+                ..
+            } if state.menu.is_some() => {
+                state.menu_key = match key {
+                    KEY_UP | KEY_K => Some(MenuKey::Up),
+                    KEY_DOWN | KEY_J => Some(MenuKey::Down),
+                    KEY_ENTER | KEY_SPACE => Some(MenuKey::Choose),
+                    KEY_ESC => Some(MenuKey::Close),
+                    _ => None,
+                };
+            }
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(wl_keyboard::KeyState::Pressed),
+                ..
+            } => match key {
+                KEY_ESC => state.running = false,
+                KEY_ENTER => {
+                    state.confirmed = true;
+                    state.running = false;
+                }
+                KEY_SPACE => state.toggle_requested = true,
+                _ => (),
+            },
+            wl_keyboard::Event::Modifiers { mods_depressed, .. } => {
+                state.shift_pressed = mods_depressed & SHIFT_MASK != 0;
             }
+            _ => (),
         }
     }
 }
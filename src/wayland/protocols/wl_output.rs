@@ -4,16 +4,13 @@ impl Dispatch<wl_output::WlOutput, ()> for super::State {
     fn event(
         _: &mut Self,
         _: &wl_output::WlOutput,
-        event: wl_output::Event,
+        _event: wl_output::Event,
         _: &(),
         _: &Connection,
         _: &QueueHandle<Self>,
     ) {
-        match event {
-            /*
-                this is where my output matching code would go if this world were nicer
-            */
-            _ => (),
-        }
+        /*
+            this is where my output matching code would go if this world were nicer
+        */
     }
 }
@@ -1,10 +1,11 @@
-use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, protocol::wl_pointer};
+use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, WEnum, protocol::wl_pointer};
 use wayland_protocols::wp::cursor_shape::v1::client::{
     wp_cursor_shape_device_v1::{self, Shape as CursorShape},
     wp_cursor_shape_manager_v1,
 };
 
 use crate::wayland::buffer_surface;
+use crate::wayland::window_selector;
 
 impl Dispatch<wl_pointer::WlPointer, ()> for super::State {
     fn event(
@@ -21,7 +22,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for super::State {
             } => {
                 if let Some(cursor_shape_manager) = &state.cursor_shape_manager {
                     let device = wp_cursor_shape_manager_v1::WpCursorShapeManagerV1::get_pointer(
-                        &cursor_shape_manager,
+                        cursor_shape_manager,
                         pointer,
                         qh,
                         (),
@@ -57,6 +58,9 @@ impl Dispatch<wl_pointer::WlPointer, ()> for super::State {
                             }
                         }
                     });
+                if let Some((monitor_id, _)) = state.pointer_surface.clone() {
+                    window_selector::focus_keyboard(state, &monitor_id);
+                }
             }
             wl_pointer::Event::Leave { .. } => {
                 state.pointer_surface = None;
@@ -68,8 +72,18 @@ impl Dispatch<wl_pointer::WlPointer, ()> for super::State {
             } => {
                 state.pointer_position = Some((surface_x, surface_y));
             }
-            wl_pointer::Event::Button { .. } => {
-                state.running = false;
+            wl_pointer::Event::Button {
+                state: WEnum::Value(wl_pointer::ButtonState::Pressed),
+                ..
+            } => {
+                if state.menu_enabled {
+                    state.menu_click = true;
+                } else if state.shift_pressed {
+                    state.toggle_requested = true;
+                } else {
+                    state.clicked = true;
+                    state.running = false;
+                }
             }
             _ => (),
         }
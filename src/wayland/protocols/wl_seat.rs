@@ -9,18 +9,16 @@ impl Dispatch<wl_seat::WlSeat, ()> for super::State {
         _: &Connection,
         qh: &QueueHandle<Self>,
     ) {
-        match event {
-            wl_seat::Event::Capabilities {
-                capabilities: WEnum::Value(capabilities),
-            } => {
-                if capabilities.contains(wl_seat::Capability::Keyboard) {
-                    seat.get_keyboard(qh, ());
-                }
-                if capabilities.contains(wl_seat::Capability::Pointer) {
-                    seat.get_pointer(qh, ());
-                }
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        {
+            if capabilities.contains(wl_seat::Capability::Keyboard) {
+                seat.get_keyboard(qh, ());
+            }
+            if capabilities.contains(wl_seat::Capability::Pointer) {
+                seat.get_pointer(qh, ());
             }
-            _ => (),
         };
     }
 }
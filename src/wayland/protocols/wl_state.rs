@@ -1,10 +1,12 @@
 use super::super::buffer_surface::BufferSurface;
+use super::super::overlay_menu::{MenuKey, OverlayMenu};
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
+use hyprland::shared::Address;
 use wayland_client::{
     delegate_noop,
-    protocol::{wl_buffer, wl_compositor, wl_output, wl_shm, wl_shm_pool, wl_surface},
+    protocol::{wl_buffer, wl_compositor, wl_shm, wl_shm_pool, wl_surface},
 };
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_shell_v1;
 
@@ -33,4 +35,21 @@ pub struct State {
     pub output_index: u8,
     pub pointer_position: Option<(f64, f64)>,
     pub pointer_surface: Option<(String, wl_surface::WlSurface)>,
+    /// The monitor whose overlay has the keyboard, it follows the pointer
+    pub keyboard_monitor: Option<String>,
+    pub shift_pressed: bool,
+    /// Shift-click or space, the hovered window gets added to or removed from the selection
+    pub toggle_requested: bool,
+    /// Windows picked with shift-click or space in the order they were picked, drawn in their own colour
+    pub selected: Vec<Address>,
+    /// Picking ended with a click rather than a key, the clicked window is part of the selection
+    pub clicked: bool,
+    /// Picking ended with enter, escape leaves both this and `clicked` unset
+    pub confirmed: bool,
+    /// Clicks open a menu next to the window instead of picking it
+    pub menu_enabled: bool,
+    pub menu: Option<OverlayMenu>,
+    /// A click while `menu_enabled`, left for the picker to decide what it means
+    pub menu_click: bool,
+    pub menu_key: Option<MenuKey>,
 }
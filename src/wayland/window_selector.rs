@@ -1,9 +1,10 @@
+use crate::hyprland_layers::LayerSurface;
 use crate::wayland::buffer_surface::ClientRegion;
 
 use super::buffer_surface::{BaseSurfaceBuffer, BufferSurface, HasOutput, InProcess};
 use super::protocols::State;
 
-use hyprland::data::{Client as HyClient, Clients as HyClients, Monitors as HyMonitors};
+use hyprland::data::{Client as HyClient, Monitor as HyMonitor};
 use hyprland::shared::WorkspaceId;
 use wayland_client::EventQueue;
 
@@ -41,13 +42,9 @@ impl Dispatch<wl_registry::WlRegistry, ()> for State {
                     let shm = registry.bind::<wl_shm::WlShm, _, _>(name, 1, qh, ());
                     if let Some(compositor) = &state.compositor {
                         state.buffer_surfaces.iter_mut().for_each(|(_, bfs)| {
-                            match bfs {
-                                BufferSurface::Pre(pre) => {
-                                    let update =
-                                        InProcess::from((pre.clone(), &shm, qh, compositor));
-                                    *bfs = BufferSurface::InProcess(update);
-                                }
-                                _ => (),
+                            if let BufferSurface::Pre(pre) = bfs {
+                                let update = InProcess::from((pre.clone(), &shm, qh, compositor));
+                                *bfs = BufferSurface::InProcess(update);
                             };
                         });
                     }
@@ -91,23 +88,20 @@ impl Dispatch<wl_registry::WlRegistry, ()> for State {
                             If the is language had WeakMap i could at least us that to match outputs in the event
                             that gets fired after initially attaching but oh well \(;-;)/
                         */
-                        if let Some(buffer_surface) =
+                        if let Some(BufferSurface::InProcess(in_process)) =
                             state.buffer_surfaces.get(&state.output_index.to_string())
                         {
-                            match buffer_surface {
-                                BufferSurface::InProcess(in_process) => {
-                                    let in_process = in_process.clone();
-
-                                    let has_output =
-                                        HasOutput::from((in_process, layer_shell, &output, qh));
-                                    *state
-                                        .buffer_surfaces
-                                        .get_mut(&state.output_index.to_string())
-                                        .unwrap() = BufferSurface::HasOutput(has_output);
-                                    state.output_index += 1;
-                                }
-                                _ => (),
-                            }
+                            let in_process = in_process.clone();
+
+                            let has_output =
+                                HasOutput::from((in_process, layer_shell, &output, qh));
+                            let has_output = BufferSurface::HasOutput(has_output);
+                            let monitor_id = state.output_index.to_string();
+                            has_output.set_keyboard_focus(
+                                state.keyboard_monitor.as_ref() == Some(&monitor_id),
+                            );
+                            *state.buffer_surfaces.get_mut(&monitor_id).unwrap() = has_output;
+                            state.output_index += 1;
                         }
                     }
                 }
@@ -147,9 +141,24 @@ fn is_pixel_in_window_bounds((x_cord, y_cord): (i16, i16), client: ClientRegion)
     return false;
 } */
 
-pub fn create_state_and_region_bounds<'c>(
-    clients: &'c HyClients,
-    monitors: &HyMonitors,
+fn layer_region(layer_surface: &LayerSurface, monitors: &[HyMonitor]) -> Option<ClientRegion> {
+    let monitor = monitors
+        .iter()
+        .find(|monitor| monitor.name == layer_surface.monitor)?;
+    let layer = &layer_surface.layer;
+    return Some(ClientRegion {
+        at: ((layer.x - monitor.x) as i16, (layer.y - monitor.y) as i16),
+        size: (layer.w, layer.h),
+        monitor: Some(monitor.id.to_string()),
+        client_id: layer.address.clone(),
+    });
+}
+
+/// The returned regions are in the order they should be hit tested in, so layers drawn above windows come first
+pub fn create_state_and_region_bounds(
+    clients: &[HyClient],
+    monitors: &[HyMonitor],
+    layers: &[LayerSurface],
 ) -> (State, Vec<ClientRegion>) {
     let active_workspaces_ids: Vec<WorkspaceId> = monitors
         .iter()
@@ -167,36 +176,50 @@ pub fn create_state_and_region_bounds<'c>(
         .collect();
 
     let client_regions = clients.iter().map(|client| {
-        if let Some(client_monitor_id) = client.monitor {
-            if let Some(monitor) = monitors
+        if let Some(client_monitor_id) = client.monitor
+            && let Some(monitor) = monitors
                 .iter()
                 .find(|monitor| monitor.id == client_monitor_id)
-            {
-                let relative_x = (client.at.0 as i32) - monitor.x;
-                let relative_y = (client.at.1 as i32) - monitor.y;
-                return ClientRegion {
-                    at: (relative_x as i16, relative_y as i16),
-                    size: client.size.clone(),
-                    monitor: Some(client_monitor_id.to_string()),
-                    client_id: client.address.clone(),
-                };
-            }
+        {
+            let relative_x = (client.at.0 as i32) - monitor.x;
+            let relative_y = (client.at.1 as i32) - monitor.y;
+            return ClientRegion {
+                at: (relative_x as i16, relative_y as i16),
+                size: client.size,
+                monitor: Some(client_monitor_id.to_string()),
+                client_id: client.address.clone(),
+            };
         }
         return ClientRegion {
-            at: client.at.clone(),
-            size: client.size.clone(),
+            at: client.at,
+            size: client.size,
             monitor: None,
             client_id: client.address.clone(),
         };
     });
 
+    let (layers_above, layers_below): (Vec<&LayerSurface>, Vec<&LayerSurface>) = layers
+        .iter()
+        .partition(|layer_surface| layer_surface.is_above_windows());
+    let regions: Vec<ClientRegion> = layers_above
+        .into_iter()
+        .filter_map(|layer_surface| layer_region(layer_surface, monitors))
+        .chain(client_regions)
+        .chain(
+            layers_below
+                .into_iter()
+                .filter_map(|layer_surface| layer_region(layer_surface, monitors)),
+        )
+        .collect();
+
     let buffer_surfaces = HashMap::from_iter(monitors.iter().map(|monitor| {
-        let monitor_clients: Vec<ClientRegion> = client_regions
-            .clone()
+        let monitor_clients: Vec<ClientRegion> = regions
+            .iter()
             .filter(|client| match client.monitor.clone() {
                 Some(client_monitor) => monitor.id.to_string() == client_monitor,
                 None => false,
             })
+            .cloned()
             .collect();
 
         return (
@@ -221,8 +244,23 @@ pub fn create_state_and_region_bounds<'c>(
             output_index: 0,
             pointer_position: None,
             pointer_surface: None,
+            // Hyprland's focused monitor until the pointer goes to another one
+            keyboard_monitor: monitors
+                .iter()
+                .find(|monitor| monitor.focused)
+                .or(monitors.first())
+                .map(|monitor| monitor.id.to_string()),
+            shift_pressed: false,
+            toggle_requested: false,
+            selected: vec![],
+            clicked: false,
+            confirmed: false,
+            menu_enabled: false,
+            menu: None,
+            menu_click: false,
+            menu_key: None,
         },
-        client_regions.collect(),
+        regions,
     );
 }
 
@@ -237,3 +275,39 @@ pub fn create_wayland_window_select() -> EventQueue<State> {
 
     return event_queue;
 }
+
+/// Draws the overlay on the monitor with `monitor_id` again, after something on it changed
+pub fn redraw(state: &mut State, qh: &QueueHandle<State>, monitor_id: &str) {
+    let Some(shm) = state.shm.clone() else {
+        return;
+    };
+    if let Some(BufferSurface::ReadyToDraw(ready_to_draw)) =
+        state.buffer_surfaces.get_mut(monitor_id)
+    {
+        ready_to_draw.actually_draw_buffer_surface(&shm, qh, state.menu.as_ref(), &state.selected);
+    }
+}
+
+/// Size of the overlay on the monitor with `monitor_id` once it's been drawn
+pub fn surface_size(state: &State, monitor_id: &str) -> Option<(u16, u16)> {
+    return match state.buffer_surfaces.get(monitor_id)? {
+        BufferSurface::ReadyToDraw(ready_to_draw) => Some(ready_to_draw.size),
+        _ => None,
+    };
+}
+
+/// Moves the keyboard to the overlay on the monitor with `monitor_id`
+pub fn focus_keyboard(state: &mut State, monitor_id: &str) {
+    if state.keyboard_monitor.as_deref() == Some(monitor_id) {
+        return;
+    }
+    if let Some(previous) = state.keyboard_monitor.take()
+        && let Some(buffer_surface) = state.buffer_surfaces.get(&previous)
+    {
+        buffer_surface.set_keyboard_focus(false);
+    }
+    if let Some(buffer_surface) = state.buffer_surfaces.get(monitor_id) {
+        buffer_surface.set_keyboard_focus(true);
+    }
+    state.keyboard_monitor = Some(monitor_id.to_string());
+}
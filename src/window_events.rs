@@ -0,0 +1,102 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use color_eyre::eyre;
+use hyprland::data::{Client, FullscreenMode, WorkspaceBasic};
+use hyprland::event_listener::{
+    EventListener, WindowFloatEventData, WindowMoveEvent, WindowOpenEvent, WindowTitleEventData,
+};
+use hyprland::instance::Instance;
+use hyprland::shared::Address;
+
+/// Window events from Hyprland's event socket
+#[derive(Debug, Clone)]
+pub enum WindowEvent {
+    Opened(WindowOpenEvent),
+    Closed(Address),
+    TitleChanged(WindowTitleEventData),
+    FloatChanged(WindowFloatEventData),
+    Moved(WindowMoveEvent),
+    /// Hyprland doesn't say which window, it's the focused one
+    Fullscreen(bool),
+    Focused(Option<Address>),
+    ConfigReloaded,
+}
+
+fn forward(sender: &Sender<eyre::Result<WindowEvent>>) -> impl Fn(WindowEvent) + 'static {
+    let sender = sender.clone();
+    return move |event| {
+        let _ = sender.send(Ok(event));
+    };
+}
+
+/// Listens on `instance`'s event socket from another thread since the listener blocks forever,
+/// the channel is closed when the socket is. `Instance::from_base_socket_path` can point it at a fake socket.
+pub fn listen(instance: Instance) -> Receiver<eyre::Result<WindowEvent>> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut listener = EventListener::new();
+        let send = forward(&sender);
+        listener.add_window_opened_handler(move |event| send(WindowEvent::Opened(event)));
+        let send = forward(&sender);
+        listener.add_window_closed_handler(move |address| send(WindowEvent::Closed(address)));
+        let send = forward(&sender);
+        listener
+            .add_window_title_changed_handler(move |event| send(WindowEvent::TitleChanged(event)));
+        let send = forward(&sender);
+        // hyprland-rs reads `0` as floating but Hyprland sends `1` for floating windows
+        listener.add_float_state_changed_handler(move |event| {
+            send(WindowEvent::FloatChanged(WindowFloatEventData {
+                floating: !event.floating,
+                ..event
+            }))
+        });
+        let send = forward(&sender);
+        listener.add_window_moved_handler(move |event| send(WindowEvent::Moved(event)));
+        let send = forward(&sender);
+        listener.add_fullscreen_state_changed_handler(move |state| {
+            send(WindowEvent::Fullscreen(state))
+        });
+        let send = forward(&sender);
+        listener.add_active_window_changed_handler(move |event| {
+            send(WindowEvent::Focused(event.map(|window| window.address)))
+        });
+        let send = forward(&sender);
+        listener.add_config_reloaded_handler(move || send(WindowEvent::ConfigReloaded));
+
+        if let Err(error) = listener.instance_start_listener(&instance) {
+            let _ = sender.send(Err(eyre::eyre!("Event socket: {}", error)));
+        }
+    });
+
+    return receiver;
+}
+
+/// What can be known about a window from its `openwindow` event, for windows that closed before they could be looked up
+pub fn client_from_event(event: &WindowOpenEvent) -> Client {
+    return Client {
+        address: event.window_address.clone(),
+        at: (0, 0),
+        size: (0, 0),
+        workspace: WorkspaceBasic {
+            id: 0,
+            name: event.workspace_name.clone(),
+        },
+        floating: false,
+        fullscreen: FullscreenMode::None,
+        fullscreen_client: FullscreenMode::None,
+        monitor: None,
+        initial_class: event.window_class.clone(),
+        class: event.window_class.clone(),
+        initial_title: event.window_title.clone(),
+        title: event.window_title.clone(),
+        pid: 0,
+        xwayland: false,
+        pinned: false,
+        grouped: vec![],
+        mapped: false,
+        swallowing: None,
+        focus_history_id: -1,
+    };
+}
@@ -0,0 +1,108 @@
+use clap::ValueEnum;
+use hyprland::data::{Client, Monitor, Transforms};
+
+use crate::hyprland_config::{Length, WindowMode, WorkspaceTarget};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MonitorMatcher {
+    /// Connector name like `DP-1`, can change when monitors get plugged into different ports
+    Name,
+    /// `desc:` followed by the make, model and serial
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GeometryForm {
+    /// `size W H` and `move X Y` in pixels
+    Pixels,
+    /// `size W% H%` and `move X% Y%` relative to the monitor
+    Percent,
+    /// `size W H` in pixels and `center`
+    Center,
+}
+
+/// Size of the monitor in the same logical coordinates hyprctl uses for clients
+pub fn monitor_logical_size(monitor: &Monitor) -> (f32, f32) {
+    let (width, height) = (
+        monitor.width as f32 / monitor.scale,
+        monitor.height as f32 / monitor.scale,
+    );
+    return match monitor.transform {
+        Transforms::Normal90
+        | Transforms::Normal270
+        | Transforms::Flipped90
+        | Transforms::Flipped270 => (height, width),
+        _ => (width, height),
+    };
+}
+
+/// Client position relative to the top left corner of `monitor`
+pub fn position_on_monitor(client: &Client, monitor: Option<&Monitor>) -> (i32, i32) {
+    return match monitor {
+        Some(monitor) => (
+            client.at.0 as i32 - monitor.x,
+            client.at.1 as i32 - monitor.y,
+        ),
+        None => (client.at.0 as i32, client.at.1 as i32),
+    };
+}
+
+fn to_percent(value: i32, of: f32) -> Length {
+    return Length::Percent((value as f32 / of * 100.0).round() as i32);
+}
+
+/// Turns a length from a rule back into pixels on `monitor`
+pub fn to_pixels(length: Length, of: f32) -> i32 {
+    return match length {
+        Length::Pixels(pixels) => pixels,
+        Length::Percent(percent) => (percent as f32 / 100.0 * of).round() as i32,
+    };
+}
+
+/// The rules that put a window back where `client` currently is
+pub fn geometry_modes(
+    client: &Client,
+    monitor: Option<&Monitor>,
+    form: GeometryForm,
+) -> Vec<WindowMode> {
+    let (width, height) = (client.size.0 as i32, client.size.1 as i32);
+    let (x, y) = position_on_monitor(client, monitor);
+
+    return match (form, monitor) {
+        (GeometryForm::Percent, Some(monitor)) => {
+            let (monitor_width, monitor_height) = monitor_logical_size(monitor);
+            vec![
+                WindowMode::Size(
+                    to_percent(width, monitor_width),
+                    to_percent(height, monitor_height),
+                ),
+                WindowMode::Move(to_percent(x, monitor_width), to_percent(y, monitor_height)),
+            ]
+        }
+        (GeometryForm::Center, _) => vec![
+            WindowMode::Size(Length::Pixels(width), Length::Pixels(height)),
+            WindowMode::Center,
+        ],
+        // without a monitor there is nothing to take a percentage of
+        (GeometryForm::Pixels, _) | (GeometryForm::Percent, None) => vec![
+            WindowMode::Size(Length::Pixels(width), Length::Pixels(height)),
+            WindowMode::Move(Length::Pixels(x), Length::Pixels(y)),
+        ],
+    };
+}
+
+/// How the monitor is written in rules, window and workspace rules take the same form
+pub fn monitor_selector(monitor: &Monitor, matcher: MonitorMatcher) -> String {
+    return match matcher {
+        MonitorMatcher::Name => monitor.name.clone(),
+        MonitorMatcher::Desc => format!("desc:{}", monitor.description),
+    };
+}
+
+pub fn monitor_mode(monitor: &Monitor, matcher: MonitorMatcher) -> WindowMode {
+    return WindowMode::Monitor(monitor_selector(monitor, matcher));
+}
+
+pub fn workspace_mode(client: &Client, silent: bool) -> WindowMode {
+    return WindowMode::Workspace(WorkspaceTarget::from(&client.workspace), silent);
+}